@@ -4,6 +4,7 @@
 //! mu classes
 #![allow(unused_braces)]
 #![allow(clippy::identity_op)]
+#![allow(clippy::new_without_default)]
 use {
     crate::{
         core::{exception, frame::Frame, mu::Mu},
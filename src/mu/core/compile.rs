@@ -10,43 +10,246 @@ use {
             classes::{Tag, Type},
             exception,
             exception::{Condition, Exception, Result},
+            frame::Frame,
             mu::Mu,
+            namespace::Core as _,
+            vm::Bytecode,
         },
         types::{
             cons::{Cons, Core as _, ProperListIter},
             fixnum::Fixnum,
-            function::Function,
+            function::{Arity, Function},
             namespace::{Core as _, Namespace, Scope},
+            r#struct::Struct,
             symbol::{Core as _, Symbol},
+            vector::{Core as _, Vector},
         },
     },
-    std::cell::{Ref, RefMut},
+    std::cell::RefMut,
 };
 
 // special forms
 type SpecFn = fn(&Mu, Tag) -> exception::Result<Tag>;
+
+// frame id, lexical symbols, frames captured (None if it can't escape),
+// symbols referenced
+pub type LexicalFrame = (Tag, Vec<Tag>, Option<Vec<Tag>>, Vec<bool>);
 type SpecMap = (Tag, SpecFn);
 
 lazy_static! {
+    // natives without side effects, folded when their arguments are
+    // constant. none of them panic, fixnum overflow raises :range
+    static ref PURE: Vec<&'static str> = vec![
+        "car", "cdr", "eq", "fl-add", "fl-div", "fl-lt", "fl-mul", "fl-sub", "fx-add", "fx-div",
+        "fx-lt", "fx-mul", "fx-sub", "length", "logand", "logor", "nth", "nthcdr", "type-of",
+    ];
+
     static ref SPECMAP: Vec<SpecMap> = vec![
+        (Symbol::keyword("block"), Mu::compile_block),
+        (Symbol::keyword("catch"), Mu::compile_catch),
         (Symbol::keyword("if"), Mu::compile_if),
         (Symbol::keyword("lambda"), Mu::compile_lambda),
+        (Symbol::keyword("let"), Mu::compile_let),
+        (Symbol::keyword("let*"), Mu::compile_let_seq),
+        (Symbol::keyword("macro"), Mu::compile_macro),
+        (Symbol::keyword("mv-bind"), Mu::compile_mv_bind),
+        (Symbol::keyword("mv-list"), Mu::compile_mv_list),
+        (Symbol::keyword("progv"), Mu::compile_progv),
+        (Symbol::keyword("protect"), Mu::compile_protect),
         (Symbol::keyword("quote"), Mu::compile_quote),
+        (Symbol::keyword("return"), Mu::compile_return),
+        (Symbol::keyword("setq"), Mu::compile_setq),
     ];
 }
 
 pub trait Compiler {
     fn compile(_: &Mu, _: Tag) -> exception::Result<Tag>;
-    fn compile_frame_symbols(_: &Mu, _: Tag) -> exception::Result<Vec<Tag>>;
+    fn compile_block(_: &Mu, _: Tag) -> exception::Result<Tag>;
+    fn compile_call(_: &Mu, _: Tag, _: Tag) -> exception::Result<()>;
+    fn compile_catch(_: &Mu, _: Tag) -> exception::Result<Tag>;
+    fn compile_frame_symbols(_: &Mu, _: Tag)
+        -> exception::Result<(Vec<Tag>, usize, Option<Arity>)>;
+    fn compile_function(_: &Mu, _: Tag, _: Tag, _: bool) -> exception::Result<Tag>;
     fn compile_if(_: &Mu, _: Tag) -> exception::Result<Tag>;
+    fn compile_fold(_: &Mu, _: Tag, _: Tag) -> Option<Tag>;
+    fn constant_of(_: &Mu, _: Tag) -> Option<Tag>;
     fn compile_lambda(_: &Mu, _: Tag) -> exception::Result<Tag>;
+    fn compile_let(_: &Mu, _: Tag) -> exception::Result<Tag>;
+    fn compile_let_seq(_: &Mu, _: Tag) -> exception::Result<Tag>;
+    fn compile_let_slots(_: &Mu, _: Tag, _: bool) -> exception::Result<Tag>;
     fn compile_lexical(_: &Mu, _: Tag) -> Result<Tag>;
+    fn lexical_ref(_: &Mu, _: Tag) -> Option<(Tag, usize)>;
     fn compile_list(_: &Mu, _: Tag) -> exception::Result<Tag>;
+    fn compile_macro(_: &Mu, _: Tag) -> exception::Result<Tag>;
+    fn compile_mv_bind(_: &Mu, _: Tag) -> exception::Result<Tag>;
+    fn compile_mv_list(_: &Mu, _: Tag) -> exception::Result<Tag>;
+    fn macro_function(_: &Mu, _: Tag) -> Option<Tag>;
+    fn macroexpand_1(_: &Mu, _: Tag) -> exception::Result<Option<Tag>>;
+    fn macroexpand(_: &Mu, _: Tag) -> exception::Result<Tag>;
+    fn compile_progv(_: &Mu, _: Tag) -> exception::Result<Tag>;
+    fn compile_protect(_: &Mu, _: Tag) -> exception::Result<Tag>;
     fn compile_quote(_: &Mu, _: Tag) -> exception::Result<Tag>;
+    fn compile_return(_: &Mu, _: Tag) -> exception::Result<Tag>;
+    fn compile_setq(_: &Mu, _: Tag) -> exception::Result<Tag>;
     fn compile_special_form(_: &Mu, _: Tag, args: Tag) -> exception::Result<Tag>;
 }
 
 impl Compiler for Mu {
+    // a block is a catch on an uninterned tag only its :return forms can see
+    fn compile_block(mu: &Mu, args: Tag) -> exception::Result<Tag> {
+        if Cons::length(mu, args) < 1 || Tag::type_of(mu, Cons::car(mu, args)) != Type::Symbol {
+            return Err(Exception::new(
+                Condition::Syntax,
+                "compile::compile_block",
+                args,
+            ));
+        }
+
+        let block_tag = Symbol::new(mu, Tag::nil(), Scope::Extern, "block", Tag::nil()).evict(mu);
+
+        mu.blocks
+            .borrow_mut()
+            .push((Cons::car(mu, args), block_tag));
+        let thunk = Self::compile_function(mu, Tag::nil(), Cons::cdr(mu, args), false);
+        mu.blocks.borrow_mut().pop();
+
+        let catch_vec = vec![
+            Namespace::intern(mu, mu.mu_ns, Scope::Intern, "catch".to_string(), Tag::nil()),
+            Cons::list(mu, &[Symbol::keyword("quote"), block_tag]),
+            thunk?,
+        ];
+
+        Self::compile(mu, Cons::list(mu, &catch_vec))
+    }
+
+    // warn about calls that can't succeed as compiled, the function
+    // position of a call isn't a lexical reference
+    fn compile_call(mu: &Mu, func: Tag, args: Tag) -> exception::Result<()> {
+        if Tag::type_of(mu, func) == Type::Symbol {
+            for (_, symbols, _, refs) in mu.compile.borrow_mut().iter_mut().rev() {
+                if let Some(nth) = symbols.iter().rposition(|lex| func.eq_(*lex)) {
+                    refs[nth] = true;
+                    return Ok(());
+                }
+            }
+
+            // a later form may define it, see Mu::warn_undefined
+            if Symbol::is_unbound(mu, func) {
+                let mut undefined_ref = mu.undefined.borrow_mut();
+
+                if !undefined_ref.iter().any(|symbol| symbol.eq_(func)) {
+                    undefined_ref.push(func)
+                }
+
+                return Ok(());
+            }
+        }
+
+        let fnc = match Tag::type_of(mu, func) {
+            Type::Symbol => Symbol::value_of(mu, func),
+            _ => func,
+        };
+
+        if Tag::type_of(mu, fnc) != Type::Function {
+            return Ok(());
+        }
+
+        let nargs = Cons::length(mu, args);
+        let nreqs = Fixnum::as_i64(mu, Function::nreq_of(mu, fnc)) as usize;
+        let arity_ok = match Arity::from_tag(mu, Function::arity_of(mu, fnc)) {
+            Some(arity) => {
+                let nkeys = nargs.saturating_sub(nreqs + arity.nopt);
+
+                nargs >= nreqs
+                    && (arity.rest || !arity.keys.is_empty() || nargs <= nreqs + arity.nopt)
+                    && (arity.keys.is_empty() || nkeys % 2 == 0)
+            }
+            None => nargs == nreqs,
+        };
+
+        if arity_ok {
+            Ok(())
+        } else {
+            mu.warn(
+                Condition::Arity,
+                "compile: wrong number of arguments",
+                Cons::new(func, args).evict(mu),
+            )
+        }
+    }
+
+    // the value of a compiled form that evaluates to a constant
+    fn constant_of(mu: &Mu, form: Tag) -> Option<Tag> {
+        match Tag::type_of(mu, form) {
+            Type::Symbol => None,
+            Type::Cons if Cons::car(mu, form).eq_(Symbol::keyword("quote")) => {
+                Some(Cons::nth(mu, 1, form).unwrap())
+            }
+            Type::Cons => None,
+            _ => Some(form),
+        }
+    }
+
+    // a call to a pure native with constant arguments compiles to its
+    // value. calls that raise are left for the runtime to report
+    fn compile_fold(mu: &Mu, func: Tag, arglist: Tag) -> Option<Tag> {
+        if Tag::type_of(mu, func) != Type::Symbol
+            || Symbol::is_unbound(mu, func)
+            || !Symbol::namespace_of(mu, func).eq_(mu.mu_ns)
+        {
+            return None;
+        }
+
+        let fnc = Symbol::value_of(mu, func);
+        if Tag::type_of(mu, fnc) != Type::Function
+            || Tag::type_of(mu, Function::form_of(mu, fnc)) != Type::Fixnum
+        {
+            return None;
+        }
+
+        let (name, _, nreqs, native) =
+            Mu::map_core(Fixnum::as_i64(mu, Function::form_of(mu, fnc)) as usize);
+        if !PURE.contains(&name) || Cons::length(mu, arglist) != nreqs as usize {
+            return None;
+        }
+
+        let argv = ProperListIter::new(mu, arglist)
+            .map(|cons| Self::constant_of(mu, Cons::car(mu, cons)))
+            .collect::<Option<Vec<Tag>>>()?;
+
+        let mut fp = Frame {
+            func: fnc,
+            argv,
+            value: Tag::nil(),
+        };
+
+        native(mu, &mut fp).ok()?;
+        match Tag::type_of(mu, fp.value) {
+            Type::Symbol | Type::Cons => {
+                Some(Cons::list(mu, &[Symbol::keyword("quote"), fp.value]))
+            }
+            _ => Some(fp.value),
+        }
+    }
+
+    fn compile_catch(mu: &Mu, args: Tag) -> exception::Result<Tag> {
+        if Cons::length(mu, args) < 1 {
+            return Err(Exception::new(
+                Condition::Syntax,
+                "compile::compile_catch",
+                args,
+            ));
+        }
+
+        let catch_vec = vec![
+            Namespace::intern(mu, mu.mu_ns, Scope::Intern, "catch".to_string(), Tag::nil()),
+            Cons::car(mu, args),
+            Self::compile_function(mu, Tag::nil(), Cons::cdr(mu, args), false)?,
+        ];
+
+        Self::compile(mu, Cons::list(mu, &catch_vec))
+    }
+
     fn compile_if(mu: &Mu, args: Tag) -> exception::Result<Tag> {
         if Cons::length(mu, args) != 3 {
             return Err(Exception::new(
@@ -56,39 +259,135 @@ impl Compiler for Mu {
             ));
         }
 
-        let lambda = Symbol::keyword("lambda");
+        let test = Self::compile(mu, Cons::car(mu, args))?;
+        let consequent = Self::compile(mu, Cons::nth(mu, 1, args).unwrap())?;
+        let alternate = Self::compile(mu, Cons::nth(mu, 2, args).unwrap())?;
 
-        let if_vec = vec![
-            Namespace::intern(mu, mu.mu_ns, Scope::Intern, "if".to_string(), Tag::nil()),
-            match Cons::nth(mu, 0, args) {
-                Some(t) => t,
-                None => panic!(),
-            },
-            Cons::list(
+        // a constant test selects its arm at compile time. both arms are
+        // compiled first so the dead one is still checked and warned about
+        if let Some(value) = Self::constant_of(mu, test) {
+            return Ok(if value.null_() { alternate } else { consequent });
+        }
+
+        // the arms are compiled in place, eval and the machine select one
+        let if_vec = vec![Symbol::keyword("if"), test, consequent, alternate];
+
+        Ok(Cons::list(mu, &if_vec))
+    }
+
+    // (:mv-bind (symbol ...) form . body), body runs with symbols bound to form's values
+    fn compile_mv_bind(mu: &Mu, args: Tag) -> exception::Result<Tag> {
+        let symbols = Cons::car(mu, args);
+
+        if Cons::length(mu, args) < 2
+            || !matches!(Tag::type_of(mu, symbols), Type::Null | Type::Cons)
+        {
+            return Err(Exception::new(
+                Condition::Syntax,
+                "compile::compile_mv_bind",
+                args,
+            ));
+        }
+
+        let (_, _, arity) = Self::compile_frame_symbols(mu, symbols)?;
+        if arity.is_some() {
+            return Err(Exception::new(
+                Condition::Syntax,
+                "compile::compile_mv_bind",
+                symbols,
+            ));
+        }
+
+        let mv_vec = vec![
+            Namespace::intern(
                 mu,
-                &[
-                    lambda,
-                    Tag::nil(),
-                    match Cons::nth(mu, 1, args) {
-                        Some(t) => t,
-                        None => panic!(),
-                    },
-                ],
+                mu.mu_ns,
+                Scope::Intern,
+                "mv-bind".to_string(),
+                Tag::nil(),
             ),
-            Cons::list(
+            Self::compile_function(mu, symbols, Cons::nthcdr(mu, 2, args).unwrap(), false)?,
+            Self::compile_function(
                 mu,
-                &[
-                    lambda,
-                    Tag::nil(),
-                    match Cons::nth(mu, 2, args) {
-                        Some(t) => t,
-                        None => panic!(),
-                    },
-                ],
+                Tag::nil(),
+                Cons::list(mu, &[Cons::nth(mu, 1, args).unwrap()]),
+                false,
+            )?,
+        ];
+
+        Ok(Cons::list(mu, &mv_vec))
+    }
+
+    fn compile_mv_list(mu: &Mu, args: Tag) -> exception::Result<Tag> {
+        if Cons::length(mu, args) != 1 {
+            return Err(Exception::new(
+                Condition::Syntax,
+                "compile::compile_mv_list",
+                args,
+            ));
+        }
+
+        let mv_vec = vec![
+            Namespace::intern(
+                mu,
+                mu.mu_ns,
+                Scope::Intern,
+                "mv-list".to_string(),
+                Tag::nil(),
             ),
+            Self::compile_function(mu, Tag::nil(), args, false)?,
         ];
 
-        Self::compile(mu, Cons::list(mu, &if_vec))
+        Ok(Cons::list(mu, &mv_vec))
+    }
+
+    // (:progv symbols values . body), body runs in a thunk that doesn't escape
+    fn compile_progv(mu: &Mu, args: Tag) -> exception::Result<Tag> {
+        if Cons::length(mu, args) < 2 {
+            return Err(Exception::new(
+                Condition::Syntax,
+                "compile::compile_progv",
+                args,
+            ));
+        }
+
+        let progv_vec = vec![
+            Namespace::intern(mu, mu.mu_ns, Scope::Intern, "progv".to_string(), Tag::nil()),
+            Cons::car(mu, args),
+            Cons::nth(mu, 1, args).unwrap(),
+            Self::compile_function(mu, Tag::nil(), Cons::nthcdr(mu, 2, args).unwrap(), false)?,
+        ];
+
+        Self::compile(mu, Cons::list(mu, &progv_vec))
+    }
+
+    fn compile_protect(mu: &Mu, args: Tag) -> exception::Result<Tag> {
+        if Cons::length(mu, args) < 1 {
+            return Err(Exception::new(
+                Condition::Syntax,
+                "compile::compile_protect",
+                args,
+            ));
+        }
+
+        let protect_vec = vec![
+            Namespace::intern(
+                mu,
+                mu.mu_ns,
+                Scope::Intern,
+                "protect".to_string(),
+                Tag::nil(),
+            ),
+            Self::compile_function(
+                mu,
+                Tag::nil(),
+                Cons::list(mu, &[Cons::car(mu, args)]),
+                false,
+            )?,
+            Self::compile_function(mu, Tag::nil(), Cons::cdr(mu, args), false)?,
+        ];
+
+        Self::compile(mu, Cons::list(mu, &protect_vec))
     }
 
     fn compile_quote(mu: &Mu, args: Tag) -> exception::Result<Tag> {
@@ -103,6 +402,44 @@ impl Compiler for Mu {
         Ok(Cons::new(Symbol::keyword("quote"), args).evict(mu))
     }
 
+    fn compile_return(mu: &Mu, args: Tag) -> exception::Result<Tag> {
+        let nargs = Cons::length(mu, args);
+
+        if !(1..=2).contains(&nargs) {
+            return Err(Exception::new(
+                Condition::Syntax,
+                "compile::compile_return",
+                args,
+            ));
+        }
+
+        let name = Cons::car(mu, args);
+        let block_tag = match mu
+            .blocks
+            .borrow()
+            .iter()
+            .rev()
+            .find(|(block, _)| block.eq_(name))
+        {
+            Some((_, block_tag)) => *block_tag,
+            None => {
+                return Err(Exception::new(
+                    Condition::Syntax,
+                    "compile::compile_return",
+                    name,
+                ))
+            }
+        };
+
+        let throw_vec = vec![
+            Namespace::intern(mu, mu.mu_ns, Scope::Extern, "throw".to_string(), Tag::nil()),
+            Cons::list(mu, &[Symbol::keyword("quote"), block_tag]),
+            Cons::nth(mu, 1, args).unwrap_or_else(Tag::nil),
+        ];
+
+        Self::compile(mu, Cons::list(mu, &throw_vec))
+    }
+
     fn compile_special_form(mu: &Mu, name: Tag, args: Tag) -> exception::Result<Tag> {
         match SPECMAP.iter().copied().find(|spec| name.eq_(spec.0)) {
             Some(spec) => spec.1(mu, args),
@@ -129,41 +466,154 @@ impl Compiler for Mu {
     }
 
     // lexical symbols
-    fn compile_frame_symbols(mu: &Mu, lambda: Tag) -> exception::Result<Vec<Tag>> {
-        let mut symv = Vec::new();
+    // lambda list, returns frame symbols, number of required
+    // arguments, and the shape of anything past them
+    fn compile_frame_symbols(
+        mu: &Mu,
+        lambda: Tag,
+    ) -> exception::Result<(Vec<Tag>, usize, Option<Arity>)> {
+        #[derive(PartialEq, PartialOrd)]
+        enum Section {
+            Required,
+            Optional,
+            Rest,
+            Key,
+        }
+
+        let syntax = |tag| {
+            Err(Exception::new(
+                Condition::Syntax,
+                "compile::compile_frame_symbols",
+                tag,
+            ))
+        };
+
+        let mut symv: Vec<Tag> = Vec::new();
+        let mut nreqs = 0;
+        let mut section = Section::Required;
+        let mut arity = Arity {
+            nopt: 0,
+            rest: false,
+            keys: Vec::new(),
+            defaults: Vec::new(),
+        };
 
         for cons in ProperListIter::new(mu, lambda) {
-            let symbol = Cons::car(mu, cons);
-            if Tag::type_of(mu, symbol) == Type::Symbol {
-                match symv.iter().rev().position(|lex| symbol.eq_(*lex)) {
-                    Some(_) => {
-                        return Err(Exception::new(
-                            Condition::Syntax,
-                            "compile::compile_frame_symbols",
-                            symbol,
-                        ))
+            let arg = Cons::car(mu, cons);
+
+            let (symbol, default) = match Tag::type_of(mu, arg) {
+                Type::Symbol => {
+                    let name = Vector::as_string(mu, Symbol::name_of(mu, arg));
+                    let next = match name.as_str() {
+                        "&optional" => Some(Section::Optional),
+                        "&rest" => Some(Section::Rest),
+                        "&key" => Some(Section::Key),
+                        _ => None,
+                    };
+
+                    match next {
+                        Some(next) => {
+                            if next <= section || (section == Section::Rest && !arity.rest) {
+                                return syntax(arg);
+                            }
+
+                            section = next;
+                            continue;
+                        }
+                        None => (arg, Tag::nil()),
                     }
-                    _ => symv.push(symbol),
                 }
-            } else {
-                return Err(Exception::new(
-                    Condition::Type,
-                    "compile::compile_frame_symbols",
-                    symbol,
-                ));
+                Type::Cons if section == Section::Optional || section == Section::Key => {
+                    match Cons::length(mu, arg) {
+                        1 | 2 => (Cons::car(mu, arg), Cons::nth(mu, 1, arg).unwrap()),
+                        _ => return syntax(arg),
+                    }
+                }
+                _ => {
+                    return Err(Exception::new(
+                        Condition::Type,
+                        "compile::compile_frame_symbols",
+                        arg,
+                    ))
+                }
+            };
+
+            if Tag::type_of(mu, symbol) != Type::Symbol || symv.iter().any(|lex| symbol.eq_(*lex)) {
+                return syntax(symbol);
+            }
+
+            match section {
+                Section::Required => nreqs += 1,
+                Section::Optional => {
+                    arity.nopt += 1;
+                    arity.defaults.push(default)
+                }
+                Section::Rest => {
+                    if arity.rest {
+                        return syntax(symbol);
+                    }
+
+                    arity.rest = true
+                }
+                Section::Key => {
+                    let name = Vector::as_string(mu, Symbol::name_of(mu, symbol));
+
+                    if name.is_empty() || name.len() > Tag::DIRECT_STR_MAX {
+                        return syntax(symbol);
+                    }
+
+                    arity.keys.push(Symbol::keyword(&name));
+                    arity.defaults.push(default)
+                }
             }
+
+            symv.push(symbol)
+        }
+
+        if section == Section::Rest && !arity.rest {
+            return syntax(lambda);
         }
 
-        Ok(symv)
+        Ok((
+            symv,
+            nreqs,
+            match section {
+                Section::Required => None,
+                _ => Some(arity),
+            },
+        ))
     }
 
-    fn compile_lexical(mu: &Mu, symbol: Tag) -> Result<Tag> {
-        let lexenv_ref: Ref<Vec<(Tag, Vec<Tag>)>> = mu.compile.borrow();
+    // frame id and offset of a lexical symbol
+    fn lexical_ref(mu: &Mu, symbol: Tag) -> Option<(Tag, usize)> {
+        let mut lexenv_ref: RefMut<Vec<LexicalFrame>> = mu.compile.borrow_mut();
+
+        for depth in (0..lexenv_ref.len()).rev() {
+            let (tag, symbols, _, refs) = &mut lexenv_ref[depth];
+
+            if let Some(nth) = symbols.iter().rposition(|lex| symbol.eq_(*lex)) {
+                let tag = *tag;
+                refs[nth] = true;
+
+                // lambdas between here and the reference close over the frame
+                for (_, _, captures, _) in lexenv_ref.iter_mut().skip(depth + 1) {
+                    if let Some(captures) = captures {
+                        if !captures.iter().any(|id| id.eq_(tag)) {
+                            captures.push(tag)
+                        }
+                    }
+                }
+
+                return Some((tag, nth));
+            }
+        }
 
-        for frame in lexenv_ref.iter().rev() {
-            let (tag, symbols) = frame;
+        None
+    }
 
-            if let Some(nth) = symbols.iter().position(|lex| symbol.eq_(*lex)) {
+    fn compile_lexical(mu: &Mu, symbol: Tag) -> Result<Tag> {
+        match Self::lexical_ref(mu, symbol) {
+            Some((tag, nth)) => {
                 let lex_ref = vec![
                     Namespace::intern(
                         mu,
@@ -176,14 +626,53 @@ impl Compiler for Mu {
                     Fixnum::as_tag(nth as i64),
                 ];
 
-                match <Mu as Compiler>::compile(mu, Cons::list(mu, &lex_ref)) {
-                    Ok(lexref) => return Ok(lexref),
-                    Err(e) => return Err(e),
-                }
+                <Mu as Compiler>::compile(mu, Cons::list(mu, &lex_ref))
             }
+            None => Ok(symbol),
         }
+    }
 
-        Ok(symbol)
+    fn compile_setq(mu: &Mu, args: Tag) -> exception::Result<Tag> {
+        if Cons::length(mu, args) != 2 {
+            return Err(Exception::new(
+                Condition::Syntax,
+                "compile::compile_setq",
+                args,
+            ));
+        }
+
+        let symbol = Cons::car(mu, args);
+        if Tag::type_of(mu, symbol) != Type::Symbol {
+            return Err(Exception::new(
+                Condition::Type,
+                "compile::compile_setq",
+                symbol,
+            ));
+        }
+
+        match Self::lexical_ref(mu, symbol) {
+            Some((tag, nth)) => {
+                let setv = vec![
+                    Namespace::intern(
+                        mu,
+                        mu.mu_ns,
+                        Scope::Extern,
+                        "fr-setv".to_string(),
+                        Tag::nil(),
+                    ),
+                    Fixnum::as_tag(tag.as_u64() as i64),
+                    Fixnum::as_tag(nth as i64),
+                    Self::compile(mu, Cons::nth(mu, 1, args).unwrap())?,
+                ];
+
+                Ok(Cons::list(mu, &setv))
+            }
+            None => Err(Exception::new(
+                Condition::Unbound,
+                "compile::compile_setq",
+                symbol,
+            )),
+        }
     }
 
     fn compile_lambda(mu: &Mu, args: Tag) -> exception::Result<Tag> {
@@ -211,31 +700,303 @@ impl Compiler for Mu {
             }
         };
 
+        Self::compile_function(mu, lambda, body, true)
+    }
+
+    // a macro is a :macro struct wrapping its expander, built at runtime
+    // so the expander can close over enclosing frames
+    fn compile_macro(mu: &Mu, args: Tag) -> exception::Result<Tag> {
+        let expander = Self::compile_lambda(mu, args)?;
+
+        let struct_vec = vec![
+            Namespace::intern(
+                mu,
+                mu.mu_ns,
+                Scope::Extern,
+                "struct".to_string(),
+                Tag::nil(),
+            ),
+            Symbol::keyword("macro"),
+            Cons::list(
+                mu,
+                &[
+                    Namespace::intern(mu, mu.mu_ns, Scope::Extern, "cons".to_string(), Tag::nil()),
+                    expander,
+                    Tag::nil(),
+                ],
+            ),
+        ];
+
+        Ok(Cons::list(mu, &struct_vec))
+    }
+
+    // the expander of a symbol bound to a macro, if any
+    fn macro_function(mu: &Mu, symbol: Tag) -> Option<Tag> {
+        if Tag::type_of(mu, symbol) != Type::Symbol || Symbol::is_unbound(mu, symbol) {
+            return None;
+        }
+
+        let value = Symbol::value_of(mu, symbol);
+        if Tag::type_of(mu, value) != Type::Struct
+            || !Struct::stype(mu, value).eq_(Symbol::keyword("macro"))
+        {
+            return None;
+        }
+
+        match Vector::r#ref(mu, Struct::vector(mu, value), 0) {
+            Some(expander) if Tag::type_of(mu, expander) == Type::Function => Some(expander),
+            _ => None,
+        }
+    }
+
+    // expand a macro call once, None if form isn't one. errors raised
+    // by the expander are reported against the form being expanded
+    fn macroexpand_1(mu: &Mu, form: Tag) -> exception::Result<Option<Tag>> {
+        if Tag::type_of(mu, form) != Type::Cons {
+            return Ok(None);
+        }
+
+        match Self::macro_function(mu, Cons::car(mu, form)) {
+            Some(expander) => match (Frame {
+                func: expander,
+                argv: ProperListIter::new(mu, Cons::cdr(mu, form))
+                    .map(|cons| Cons::car(mu, cons))
+                    .collect(),
+                value: Tag::nil(),
+            })
+            .apply(mu, expander)
+            {
+                Ok(expansion) => Ok(Some(expansion)),
+                Err(e) => Err(Exception { tag: form, ..e }),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn macroexpand(mu: &Mu, form: Tag) -> exception::Result<Tag> {
+        let mut form = form;
+
+        while let Some(expansion) = Self::macroexpand_1(mu, form)? {
+            form = expansion
+        }
+
+        Ok(form)
+    }
+
+    fn compile_let(mu: &Mu, args: Tag) -> exception::Result<Tag> {
+        Self::compile_let_slots(mu, args, false)
+    }
+
+    fn compile_let_seq(mu: &Mu, args: Tag) -> exception::Result<Tag> {
+        Self::compile_let_slots(mu, args, true)
+    }
+
+    // let variables are slots appended to the enclosing lambda's frame,
+    // (:let id offset (init ...) . body) sets them and runs the body.
+    // a let outside of any lambda runs in a thunk's frame.
+    fn compile_let_slots(mu: &Mu, args: Tag, sequential: bool) -> exception::Result<Tag> {
+        let bindings = Cons::car(mu, args);
+
+        if Cons::length(mu, args) < 1
+            || !matches!(Tag::type_of(mu, bindings), Type::Null | Type::Cons)
+        {
+            return Err(Exception::new(
+                Condition::Syntax,
+                "compile::compile_let",
+                args,
+            ));
+        }
+
+        if mu.compile.borrow().is_empty() {
+            let form = Cons::new(
+                Symbol::keyword(if sequential { "let*" } else { "let" }),
+                args,
+            )
+            .evict(mu);
+
+            return Ok(Cons::list(
+                mu,
+                &[Self::compile_function(
+                    mu,
+                    Tag::nil(),
+                    Cons::list(mu, &[form]),
+                    false,
+                )?],
+            ));
+        }
+
+        let mut symbols = Vec::new();
+        let mut inits = Vec::new();
+
+        for cons in ProperListIter::new(mu, bindings) {
+            let binding = Cons::car(mu, cons);
+            let (symbol, init) = match Tag::type_of(mu, binding) {
+                Type::Symbol => (binding, Tag::nil()),
+                Type::Cons if Cons::length(mu, binding) == 2 => {
+                    (Cons::car(mu, binding), Cons::nth(mu, 1, binding).unwrap())
+                }
+                _ => {
+                    return Err(Exception::new(
+                        Condition::Syntax,
+                        "compile::compile_let",
+                        binding,
+                    ))
+                }
+            };
+
+            if Tag::type_of(mu, symbol) != Type::Symbol {
+                return Err(Exception::new(
+                    Condition::Syntax,
+                    "compile::compile_let",
+                    binding,
+                ));
+            }
+
+            inits.push(Self::compile(mu, init)?);
+            symbols.push(symbol);
+
+            // let* variables are visible to the inits after them
+            if sequential {
+                let mut lexenv_ref: RefMut<Vec<LexicalFrame>> = mu.compile.borrow_mut();
+                let (_, lexicals, _, refs) = lexenv_ref.last_mut().unwrap();
+
+                lexicals.push(symbol);
+                refs.push(false);
+            }
+        }
+
+        let (id, offset) = {
+            let mut lexenv_ref: RefMut<Vec<LexicalFrame>> = mu.compile.borrow_mut();
+            let (id, lexicals, _, refs) = lexenv_ref.last_mut().unwrap();
+
+            if !sequential {
+                lexicals.extend(&symbols);
+                refs.extend(vec![false; symbols.len()]);
+            }
+
+            (*id, lexicals.len() - symbols.len())
+        };
+
+        let body = Self::compile_list(mu, Cons::nthcdr(mu, 1, args).unwrap());
+
+        // the slots stay allocated, the variables go out of scope
+        let unused: Vec<Tag> = {
+            let mut lexenv_ref: RefMut<Vec<LexicalFrame>> = mu.compile.borrow_mut();
+            let (_, lexicals, _, refs) = lexenv_ref.last_mut().unwrap();
+            let unused = (offset..lexicals.len())
+                .filter(|nth| !refs[*nth])
+                .map(|nth| lexicals[nth])
+                .collect();
+
+            for nth in offset..lexicals.len() {
+                lexicals[nth] = Tag::nil();
+                refs[nth] = true;
+            }
+
+            unused
+        };
+
+        let body = body?;
+        for symbol in unused {
+            mu.warn_unused(symbol)?
+        }
+
+        Ok(Cons::new(
+            Symbol::keyword("let"),
+            Cons::new(
+                Fixnum::as_tag(id.as_u64() as i64),
+                Cons::new(
+                    Fixnum::as_tag(offset as i64),
+                    Cons::new(Cons::list(mu, &inits), body).evict(mu),
+                )
+                .evict(mu),
+            )
+            .evict(mu),
+        )
+        .evict(mu))
+    }
+
+    // a lambda that references frames of enclosing lambdas compiles to
+    // a mu::closure call, which captures those frames when evaluated
+    fn compile_function(mu: &Mu, lambda: Tag, body: Tag, capture: bool) -> exception::Result<Tag> {
         let frame_tag = Symbol::new(mu, Tag::nil(), Scope::Extern, "lambda", Tag::nil()).evict(mu);
 
-        match Self::compile_frame_symbols(mu, lambda) {
-            Ok(lexicals) => {
-                let mut lexenv_ref: RefMut<Vec<(Tag, Vec<Tag>)>> = mu.compile.borrow_mut();
-                lexenv_ref.push((frame_tag, lexicals));
+        let (nreqs, arity) = match Self::compile_frame_symbols(mu, lambda) {
+            Ok((lexicals, nreqs, arity)) => {
+                let mut lexenv_ref: RefMut<Vec<LexicalFrame>> = mu.compile.borrow_mut();
+                let refs = vec![false; lexicals.len()];
+
+                lexenv_ref.push((frame_tag, lexicals, capture.then(Vec::new), refs));
+
+                (nreqs, arity)
             }
             Err(e) => return Err(e),
         };
 
-        let form = match Self::compile_list(mu, body) {
-            Ok(form) => Ok(Function::new(
-                lambda,
-                Fixnum::as_tag(Cons::length(mu, lambda) as i64),
-                form,
-                frame_tag,
-            )
-            .evict(mu)),
-            Err(e) => Err(e),
+        // defaults are evaluated in the callee's frame
+        let arity = match arity {
+            Some(mut arity) => match Self::compile_list(mu, Cons::list(mu, &arity.defaults)) {
+                Ok(defaults) => {
+                    arity.defaults = ProperListIter::new(mu, defaults)
+                        .map(|cons| Cons::car(mu, cons))
+                        .collect();
+                    Ok(arity.evict(mu))
+                }
+                Err(e) => Err(e),
+            },
+            None => Ok(Tag::nil()),
         };
 
-        let mut lexenv_ref: RefMut<Vec<(Tag, Vec<Tag>)>> = mu.compile.borrow_mut();
-        lexenv_ref.pop();
+        let form = arity.and_then(|arity| Ok((arity, Self::compile_list(mu, body)?)));
+
+        let (lexicals, captures, refs) = {
+            let mut lexenv_ref: RefMut<Vec<LexicalFrame>> = mu.compile.borrow_mut();
+            let (_, lexicals, captures, refs) = lexenv_ref.pop().unwrap();
 
-        form
+            (lexicals, captures.unwrap_or_default(), refs)
+        };
+
+        let (arity, form) = form?;
+
+        // the frame id knows how many slots its frames need
+        Symbol::set_value(mu, frame_tag, Fixnum::as_tag(lexicals.len() as i64));
+
+        for (symbol, _) in lexicals
+            .iter()
+            .zip(refs)
+            .filter(|(_, referenced)| !referenced)
+        {
+            mu.warn_unused(*symbol)?
+        }
+
+        let func = Function::new(
+            lambda,
+            Fixnum::as_tag(nreqs as i64),
+            form,
+            frame_tag,
+            <Mu as Bytecode>::assemble(mu, form),
+            Cons::list(mu, &captures),
+            arity,
+        )
+        .evict(mu);
+
+        if captures.is_empty() {
+            Ok(func)
+        } else {
+            Ok(Cons::list(
+                mu,
+                &[
+                    Namespace::intern(
+                        mu,
+                        mu.mu_ns,
+                        Scope::Intern,
+                        "closure".to_string(),
+                        Tag::nil(),
+                    ),
+                    func,
+                ],
+            ))
+        }
     }
 
     fn compile(mu: &Mu, expr: Tag) -> exception::Result<Tag> {
@@ -249,14 +1010,40 @@ impl Compiler for Mu {
                         Ok(form) => Ok(form),
                         Err(e) => Err(e),
                     },
-                    Type::Symbol | Type::Function => match Self::compile_list(mu, args) {
-                        Ok(arglist) => Ok(Cons::new(func, arglist).evict(mu)),
-                        Err(e) => Err(e),
+                    Type::Symbol => match Self::macroexpand_1(mu, expr)? {
+                        Some(expansion) => Self::compile(mu, expansion),
+                        None => {
+                            Self::compile_call(mu, func, args)?;
+
+                            let arglist = Self::compile_list(mu, args)?;
+                            match Self::compile_fold(mu, func, arglist) {
+                                Some(value) => Ok(value),
+                                None => Ok(Cons::new(func, arglist).evict(mu)),
+                            }
+                        }
                     },
+                    Type::Function => {
+                        Self::compile_call(mu, func, args)?;
+                        match Self::compile_list(mu, args) {
+                            Ok(arglist) => Ok(Cons::new(func, arglist).evict(mu)),
+                            Err(e) => Err(e),
+                        }
+                    }
                     Type::Cons => match Self::compile_list(mu, args) {
                         Ok(arglist) => match Self::compile(mu, func) {
                             Ok(fnc) => match Tag::type_of(mu, fnc) {
                                 Type::Function => Ok(Cons::new(fnc, arglist).evict(mu)),
+                                // applied in place, the frames it closes over are live
+                                Type::Cons => match Cons::nth(mu, 1, fnc) {
+                                    Some(fnc) if Tag::type_of(mu, fnc) == Type::Function => {
+                                        Ok(Cons::new(fnc, arglist).evict(mu))
+                                    }
+                                    _ => Err(Exception::new(
+                                        Condition::Type,
+                                        "compile::compile",
+                                        func,
+                                    )),
+                                },
                                 _ => Err(Exception::new(Condition::Type, "compile::compile", func)),
                             },
                             Err(e) => Err(e),
@@ -273,29 +1060,89 @@ impl Compiler for Mu {
 
 #[cfg(test)]
 mod tests {
-    use crate::core::{
-        classes::{Tag, Type},
-        compile::Compiler,
-        mu::{Core, Mu},
+    use crate::{
+        core::{
+            classes::Tag,
+            compile::Compiler,
+            exception::Condition,
+            mu::{Core, Mu},
+        },
+        types::{
+            stream::{Core as _, Stream},
+            vector::{Core as _, Vector},
+        },
     };
 
+    fn compile(mu: &Mu, src: &str) -> crate::core::exception::Result<Tag> {
+        <Mu as Compiler>::compile(mu, mu.read_string(src.to_string()).unwrap())
+    }
+
+    fn print(mu: &Mu, tag: Tag) -> String {
+        let stream = Stream::open_string(mu, "", false).unwrap();
+
+        mu.write(tag, true, stream).unwrap();
+        Vector::as_string(mu, Stream::get_string(mu, stream).unwrap())
+    }
+
     #[test]
     fn compile_test() {
         let mu: &Mu = &Core::new("".to_string());
 
-        match <Mu as Compiler>::compile(mu, Tag::nil()) {
-            Ok(form) => match Tag::type_of(mu, form) {
-                Type::Null => assert!(true),
-                _ => assert!(false),
-            },
-            _ => assert!(false),
+        assert!(<Mu as Compiler>::compile(mu, Tag::nil()).unwrap().null_());
+        assert!(<Mu as Compiler>::compile_list(mu, Tag::nil())
+            .unwrap()
+            .null_());
+        assert_eq!(print(mu, compile(mu, "(mu:fx-add 1 2)").unwrap()), "3");
+    }
+
+    #[test]
+    fn compile_warnings() {
+        let mu: &Mu = &Core::new("warn:error".to_string());
+        let keys = compile(
+            mu,
+            "(mu:intern (mu:map-ns \"\") :extern \"keys\" (:lambda (a &key b) (mu:cons a b)))",
+        )
+        .unwrap();
+
+        mu.eval(keys).unwrap();
+        for (src, condition, tag) in [
+            ("(mu:car 1 2)", Condition::Arity, "(mu:car 1 2)"),
+            ("(:lambda (x y) x)", Condition::Unbound, "y"),
+            ("(:let ((a 1)) 2)", Condition::Unbound, "a"),
+            ("(keys 1 :a)", Condition::Arity, "(keys 1 :a)"),
+            ("(:if :t 1 (mu:car 1 2))", Condition::Arity, "(mu:car 1 2)"),
+            ("(:if () (mu:car 1 2) 1)", Condition::Arity, "(mu:car 1 2)"),
+        ] {
+            match compile(mu, src) {
+                Err(e) => {
+                    assert!(e.condition == condition, "{src}");
+                    assert_eq!(print(mu, e.tag), tag);
+                }
+                Ok(_) => panic!("{src} compiled"),
+            }
         }
-        match <Mu as Compiler>::compile_list(mu, Tag::nil()) {
-            Ok(form) => match Tag::type_of(mu, form) {
-                Type::Null => assert!(true),
-                _ => assert!(false),
-            },
-            _ => assert!(false),
+
+        for src in [
+            "(:lambda (x) x)",
+            "(:lambda (_x y) y)",
+            "(:let ((_a 1)) 2)",
+            "(mu:with-ex (:lambda (_obj cond) cond) (:lambda () 1))",
+            "(keys 1)",
+            "(keys 1 :b 2)",
+        ] {
+            assert!(compile(mu, src).is_ok(), "{src}");
+        }
+
+        // undefined functions are reported once the forms that
+        // might define them have been compiled
+        assert!(compile(mu, "(no-such-function 1)").is_ok());
+        match mu.warn_undefined() {
+            Err(e) => {
+                assert!(e.condition == Condition::Unbound);
+                assert_eq!(print(mu, e.tag), "no-such-function");
+            }
+            Ok(_) => panic!("no-such-function is defined"),
         }
+        assert!(mu.warn_undefined().is_ok());
     }
 }
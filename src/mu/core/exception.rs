@@ -4,6 +4,7 @@
 //! mu exceptions:
 //!    Condition
 //!    Exception
+//!    Location
 //!    Result<Exception>
 use {
     crate::{
@@ -13,8 +14,12 @@ use {
             mu::{Core as _, Mu},
         },
         types::{
-            cons::{Cons, Core as _},
+            cons::{Cons, Core as _, ProperListIter},
+            fixnum::Fixnum,
+            function::Function,
+            r#struct::{Core as _, Struct},
             symbol::{Core as _, Symbol},
+            vector::{Core as _, Vector},
         },
     },
     std::fmt,
@@ -22,11 +27,36 @@ use {
 
 pub type Result<T> = std::result::Result<T, Exception>;
 
+// the repl's restart chooser, it runs after every handler has declined
+pub type Debugger = fn(&Mu, &Exception) -> Result<()>;
+
+// frames captured in an exception's backtrace, a :stack exception
+// would otherwise copy every frame up to the depth limit
+const BACKTRACE_DEPTH: usize = 32;
+
 #[derive(Clone)]
 pub struct Exception {
     pub condition: Condition,
     pub tag: Tag,
     pub source: String,
+    pub location: Option<Box<Location>>,
+    pub form: Option<Tag>,
+    pub backtrace: Option<Tag>,
+}
+
+// how a restartable call continues after signalling
+pub enum Resume {
+    Raise(Exception),
+    Retry,
+    Value(Tag),
+}
+
+// input stream position
+#[derive(Clone, Debug)]
+pub struct Location {
+    pub name: Option<String>,
+    pub line: usize,
+    pub column: usize,
 }
 
 #[derive(Eq, PartialEq, Clone, Debug)]
@@ -34,14 +64,21 @@ pub enum Condition {
     Arity,
     Except,
     Eof,
+    Interrupt,
     Error,
     Open,
     Range,
     Read,
+    Stack,
     Stream,
     Syntax,
+    System,
+    Throw,
     Type,
     Unbound,
+    Unwind,
+    // a condition type of the program's own, the bits of its keyword
+    User(u64),
     Write,
     ZeroDivide,
 }
@@ -53,11 +90,15 @@ lazy_static! {
         (Symbol::keyword("except"), Condition::Except),
         (Symbol::keyword("eof"), Condition::Eof),
         (Symbol::keyword("error"), Condition::Error),
+        (Symbol::keyword("intr"), Condition::Interrupt),
         (Symbol::keyword("open"), Condition::Open),
         (Symbol::keyword("range"), Condition::Range),
         (Symbol::keyword("read"), Condition::Read),
+        (Symbol::keyword("stack"), Condition::Stack),
         (Symbol::keyword("stream"), Condition::Stream),
         (Symbol::keyword("syntax"), Condition::Syntax),
+        (Symbol::keyword("system"), Condition::System),
+        (Symbol::keyword("throw"), Condition::Throw),
         (Symbol::keyword("type"), Condition::Type),
         (Symbol::keyword("unbound"), Condition::Unbound),
         (Symbol::keyword("write"), Condition::Write),
@@ -76,15 +117,285 @@ impl fmt::Display for Exception {
     }
 }
 
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{}:{}:{}", name, self.line, self.column),
+            None => write!(f, "line {}, column {}", self.line, self.column),
+        }
+    }
+}
+
 impl Exception {
     pub fn new(condition: Condition, src: &str, tag: Tag) -> Self {
         Exception {
             condition,
             source: src.to_string(),
             tag,
+            location: None,
+            form: None,
+            backtrace: None,
         }
     }
 
+    // handler-bind handlers run where the exception was raised,
+    // innermost first and before anything unwinds. a handler declines
+    // by returning, a with-ex between us and the handler unwinds to its
+    // own handler instead. the backtrace is captured here, it marks the
+    // exception as signalled.
+    fn run_handlers(self, mu: &Mu, restarts: &[Tag]) -> Result<Self> {
+        let mut e = self;
+
+        e.backtrace = Some(Frame::backtrace(mu, BACKTRACE_DEPTH));
+        if mu.handlers.borrow().is_empty() && mu.debugger.get().is_none() {
+            return Ok(e);
+        }
+
+        let ex = e.to_tag(mu);
+        let mut nth = mu.handlers.borrow().len();
+
+        mu.restarts.borrow_mut().extend_from_slice(restarts);
+        let value = loop {
+            if nth == 0 {
+                break match mu.debugger.get() {
+                    Some(debugger) if e.condition != Condition::Interrupt => debugger(mu, &e),
+                    _ => Ok(()),
+                };
+            }
+
+            nth -= 1;
+            let handler = mu.handlers.borrow()[nth];
+            if Tag::type_of(mu, handler) != Type::Function {
+                if Self::handles(mu, handler, &e.condition) {
+                    break Ok(());
+                }
+
+                continue;
+            }
+
+            // handlers run with the handlers outside them
+            let inner = mu.handlers.borrow_mut().split_off(nth);
+            let value = Frame {
+                func: handler,
+                argv: vec![ex],
+                value: Tag::nil(),
+            }
+            .apply(mu, handler);
+
+            mu.handlers.borrow_mut().extend(inner);
+            if let Err(e) = value {
+                break Err(e);
+            }
+        };
+
+        let len = mu.restarts.borrow().len();
+        mu.restarts.borrow_mut().truncate(len - restarts.len());
+
+        value.map(|_| e)
+    }
+
+    // signal an exception nobody can resume
+    pub fn signal(self, mu: &Mu) -> Self {
+        if self.backtrace.is_some() || self.condition == Condition::Unwind {
+            return self;
+        }
+
+        match self.run_handlers(mu, &[]) {
+            Ok(e) | Err(e) => e,
+        }
+    }
+
+    // signal an exception from a call that can be retried or
+    // replaced by a value, the :retry and :value restarts
+    pub fn signal_restartable(self, mu: &Mu) -> Resume {
+        if self.backtrace.is_some() || self.condition == Condition::Unwind {
+            return Resume::Raise(self);
+        }
+
+        let value = Cons::new(Symbol::keyword("value"), Tag::nil()).evict(mu);
+        let retry = Cons::new(Symbol::keyword("retry"), Tag::nil()).evict(mu);
+
+        match self.run_handlers(mu, &[retry, value]) {
+            Ok(e) => Resume::Raise(e),
+            Err(e) if e.condition == Condition::Unwind => {
+                let restart = Cons::car(mu, e.tag);
+                let args = Cons::cdr(mu, e.tag);
+
+                if restart.eq_(retry) {
+                    Resume::Retry
+                } else if restart.eq_(value) {
+                    Resume::Value(Cons::car(mu, args))
+                } else {
+                    Resume::Raise(e)
+                }
+            }
+            Err(e) => Resume::Raise(e),
+        }
+    }
+
+    // the names of the active restarts, innermost first
+    pub fn restarts(mu: &Mu) -> Vec<Tag> {
+        mu.restarts
+            .borrow()
+            .iter()
+            .rev()
+            .map(|restart| Cons::car(mu, *restart))
+            .collect()
+    }
+
+    // the exception that unwinds to the innermost restart named name
+    pub fn invoke_restart(mu: &Mu, name: Tag, args: &[Tag]) -> Self {
+        let restarts_ref = mu.restarts.borrow();
+
+        match restarts_ref
+            .iter()
+            .rev()
+            .find(|restart| Cons::car(mu, **restart).eq_(name))
+        {
+            Some(restart) => Exception::new(
+                Condition::Unwind,
+                "mu:invoke-restart",
+                Cons::new(*restart, Cons::list(mu, args)).evict(mu),
+            ),
+            None => Exception::new(Condition::Unbound, "mu:invoke-restart", name),
+        }
+    }
+
+    // the innermost form being evaluated. compiled code has no forms,
+    // once the exception has been signalled any form would be a caller's
+    pub fn with_form(mut self, form: Tag) -> Self {
+        if self.form.is_none() && self.backtrace.is_none() && self.condition != Condition::Unwind {
+            self.form = Some(form)
+        }
+
+        self
+    }
+
+    // #s(:exinfo #(condition tag source location form backtrace))
+    pub fn to_tag(&self, mu: &Mu) -> Tag {
+        let location = match &self.location {
+            Some(location) => Cons::list(
+                mu,
+                &[
+                    match &location.name {
+                        Some(name) => Vector::from_string(name).evict(mu),
+                        None => Tag::nil(),
+                    },
+                    Fixnum::as_tag(location.line as i64),
+                    Fixnum::as_tag(location.column as i64),
+                ],
+            ),
+            None => Tag::nil(),
+        };
+
+        let vec = vec![
+            Self::map_condkey(self.condition.clone()).unwrap_or_else(|_| Tag::nil()),
+            self.tag,
+            Vector::from_string(&self.source).evict(mu),
+            location,
+            self.form.unwrap_or_else(Tag::nil),
+            self.backtrace.unwrap_or_else(Tag::nil),
+        ];
+
+        Struct::new(mu, "exinfo".to_string(), vec).evict(mu)
+    }
+
+    // what the repl prints
+    pub fn print(&self, mu: &Mu, stream: Tag) -> Result<()> {
+        match self.condition {
+            Condition::User(keyword) => {
+                mu.write_string(
+                    format!("exception: raised from {:?}, ", self.source),
+                    stream,
+                )?;
+                mu.write(Tag::from_u64(keyword), true, stream)?;
+                mu.write_string(" condition on ".to_string(), stream)?
+            }
+            _ => mu.write_string(
+                format!(
+                    "exception: raised from {:?}, {:?} condition on ",
+                    self.source, self.condition
+                ),
+                stream,
+            )?,
+        }
+        mu.write(self.tag, true, stream)?;
+
+        if let Some(location) = &self.location {
+            mu.write_string(format!("\n  at {location}"), stream)?
+        }
+
+        if let Some(form) = self.form {
+            mu.write_string("\n  in ".to_string(), stream)?;
+            mu.write(form, true, stream)?
+        }
+
+        if let Some(backtrace) = self.backtrace {
+            for cons in ProperListIter::new(mu, backtrace)
+                .collect::<Vec<Tag>>()
+                .iter()
+                .rev()
+            {
+                mu.write_string("\n    ".to_string(), stream)?;
+                mu.write(Cons::car(mu, *cons), true, stream)?
+            }
+        }
+
+        Ok(())
+    }
+
+    // does a handler-case for conds apply to condition
+    fn handles(mu: &Mu, conds: Tag, condition: &Condition) -> bool {
+        let key = match Self::map_condkey(condition.clone()) {
+            Ok(key) => key,
+            Err(_) => return false,
+        };
+
+        match Tag::type_of(mu, conds) {
+            Type::Cons => ProperListIter::new(mu, conds).any(|cons| Cons::car(mu, cons).eq_(key)),
+            _ => conds.eq_(Symbol::keyword("t")) || conds.eq_(key),
+        }
+    }
+
+    // unwind to handler if thunk raises an exception conds applies to.
+    // one argument handlers get the whole exception, two argument
+    // handlers the raised object and the condition keyword
+    fn handler_case(mu: &Mu, conds: Tag, handler: Tag, thunk: Tag) -> Result<Tag> {
+        let mark = mu.bindings.borrow().len();
+
+        match Self::with_handler(mu, conds, thunk) {
+            Ok(value) => Ok(value),
+            Err(e) if e.condition == Condition::Unwind => Err(e),
+            Err(e) if !Self::handles(mu, conds, &e.condition) => Err(e),
+            Err(e) => {
+                // the handler runs with the caller's dynamic bindings
+                mu.unbind(mark);
+
+                let argv = match Fixnum::as_i64(mu, Function::nreq_of(mu, handler)) {
+                    1 => vec![e.to_tag(mu)],
+                    _ => vec![e.tag, Self::map_condkey(e.condition)?],
+                };
+
+                Frame {
+                    func: handler,
+                    argv,
+                    value: Tag::nil(),
+                }
+                .apply(mu, handler)
+            }
+        }
+    }
+
+    // run thunk with handler established. anything but a function
+    // marks a handler-case, and the conditions it handles
+    fn with_handler(mu: &Mu, handler: Tag, thunk: Tag) -> Result<Tag> {
+        mu.handlers.borrow_mut().push(handler);
+        let value = mu.apply(thunk, Tag::nil());
+        mu.handlers.borrow_mut().pop();
+
+        value
+    }
+
     fn map_condition(keyword: Tag) -> Result<Condition> {
         #[allow(clippy::unnecessary_to_owned)]
         let condmap = CONDMAP
@@ -102,7 +413,14 @@ impl Exception {
         }
     }
 
+    // :unwind isn't in CONDMAP, a throw can't be raised by keyword
     fn map_condkey(cond: Condition) -> Result<Tag> {
+        match cond {
+            Condition::User(keyword) => return Ok(Tag::from_u64(keyword)),
+            Condition::Unwind => return Ok(Symbol::keyword("unwind")),
+            _ => (),
+        }
+
         #[allow(clippy::unnecessary_to_owned)]
         let condmap = CONDMAP
             .to_vec()
@@ -111,7 +429,11 @@ impl Exception {
 
         match condmap {
             Some(entry) => Ok(entry.0),
-            _ => panic!(),
+            _ => Err(Exception::new(
+                Condition::Type,
+                "exception::map_condkey",
+                Tag::nil(),
+            )),
         }
     }
 }
@@ -119,6 +441,14 @@ impl Exception {
 pub trait MuFunction {
     fn mu_with_ex(mu: &Mu, fp: &mut Frame) -> Result<()>;
     fn mu_raise(mu: &Mu, fp: &mut Frame) -> Result<()>;
+    fn mu_catch(mu: &Mu, fp: &mut Frame) -> Result<()>;
+    fn mu_throw(mu: &Mu, fp: &mut Frame) -> Result<()>;
+    fn mu_protect(mu: &Mu, fp: &mut Frame) -> Result<()>;
+    fn mu_handler_bind(mu: &Mu, fp: &mut Frame) -> Result<()>;
+    fn mu_with_restart(mu: &Mu, fp: &mut Frame) -> Result<()>;
+    fn mu_invoke_restart(mu: &Mu, fp: &mut Frame) -> Result<()>;
+    fn mu_restarts(mu: &Mu, fp: &mut Frame) -> Result<()>;
+    fn mu_handler_case(mu: &Mu, fp: &mut Frame) -> Result<()>;
 }
 
 impl MuFunction for Exception {
@@ -126,44 +456,256 @@ impl MuFunction for Exception {
         let src = fp.argv[0];
         let condition = fp.argv[1];
 
+        // keywords that aren't the kernel's are the program's own
+        // conditions, a struct raised with () is a condition of its type
         match Tag::type_of(mu, condition) {
             Type::Keyword => match Self::map_condition(condition) {
                 Ok(cond) => Err(Self::new(cond, "mu:raise", src)),
-                Err(_) => Err(Self::new(Condition::Type, "mu:raise", condition)),
+                Err(_) => Err(Self::new(
+                    Condition::User(condition.as_u64()),
+                    "mu:raise",
+                    src,
+                )),
             },
+            Type::Null if Tag::type_of(mu, src) == Type::Struct => Err(Self::new(
+                Condition::User(Struct::stype(mu, src).as_u64()),
+                "mu:raise",
+                src,
+            )),
             _ => Err(Self::new(Condition::Type, "mu:raise", condition)),
         }
     }
 
-    fn mu_with_ex(mu: &Mu, fp: &mut Frame) -> Result<()> {
+    // a throw unwinds as an :unwind exception tagged (tag . value),
+    // which only the matching catch intercepts
+    fn mu_catch(mu: &Mu, fp: &mut Frame) -> Result<()> {
+        let tag = fp.argv[0];
+        let thunk = fp.argv[1];
+
+        if Tag::type_of(mu, thunk) != Type::Function {
+            return Err(Exception::new(Condition::Type, "mu::catch", thunk));
+        }
+
+        mu.catches.borrow_mut().push(tag);
+        let value = mu.apply(thunk, Tag::nil());
+        mu.catches.borrow_mut().pop();
+
+        fp.value = match value {
+            Ok(value) => value,
+            Err(e) if e.condition == Condition::Unwind && Cons::car(mu, e.tag).eq_(tag) => {
+                Cons::cdr(mu, e.tag)
+            }
+            Err(e) => return Err(e),
+        };
+
+        Ok(())
+    }
+
+    fn mu_throw(mu: &Mu, fp: &mut Frame) -> Result<()> {
+        let tag = fp.argv[0];
+        let value = fp.argv[1];
+
+        if !mu.catches.borrow().iter().any(|catch| catch.eq_(tag)) {
+            return Err(Exception::new(Condition::Throw, "mu:throw", tag));
+        }
+
+        Err(Exception::new(
+            Condition::Unwind,
+            "mu:throw",
+            Cons::new(tag, value).evict(mu),
+        ))
+    }
+
+    fn mu_protect(mu: &Mu, fp: &mut Frame) -> Result<()> {
+        let thunk = fp.argv[0];
+        let cleanup = fp.argv[1];
+
+        for func in [thunk, cleanup] {
+            if Tag::type_of(mu, func) != Type::Function {
+                return Err(Exception::new(Condition::Type, "mu::protect", func));
+            }
+        }
+
+        let value = mu.apply(thunk, Tag::nil());
+        let values = mu.values.borrow_mut().take();
+
+        mu.apply(cleanup, Tag::nil())?;
+        *mu.values.borrow_mut() = values;
+        fp.value = value?;
+
+        Ok(())
+    }
+
+    fn mu_handler_bind(mu: &Mu, fp: &mut Frame) -> Result<()> {
         let handler = fp.argv[0];
         let thunk = fp.argv[1];
 
-        fp.value = match Tag::type_of(mu, thunk) {
-            Type::Function => match Tag::type_of(mu, handler) {
-                Type::Function => match mu.apply(thunk, Tag::nil()) {
-                    Ok(v) => v,
-                    Err(e) => {
-                        let args = vec![e.tag, Self::map_condkey(e.condition).unwrap()];
-                        match mu.apply(handler, Cons::list(mu, &args)) {
-                            Ok(v) => v,
-                            Err(e) => return Err(e),
-                        }
-                    }
-                },
-                _ => return Err(Exception::new(Condition::Type, "mu:with-ex", handler)),
-            },
-            _ => return Err(Exception::new(Condition::Type, "mu:with-ex", thunk)),
+        for func in [handler, thunk] {
+            if Tag::type_of(mu, func) != Type::Function {
+                return Err(Exception::new(Condition::Type, "mu:handler-bind", func));
+            }
+        }
+
+        fp.value = Self::with_handler(mu, handler, thunk)?;
+        Ok(())
+    }
+
+    // a restart is a (name . ()) record, invoking it unwinds
+    // as an :unwind exception tagged (record . args)
+    fn mu_with_restart(mu: &Mu, fp: &mut Frame) -> Result<()> {
+        let name = fp.argv[0];
+        let func = fp.argv[1];
+        let thunk = fp.argv[2];
+
+        for func in [func, thunk] {
+            if Tag::type_of(mu, func) != Type::Function {
+                return Err(Exception::new(Condition::Type, "mu:with-restart", func));
+            }
+        }
+
+        let restart = Cons::new(name, Tag::nil()).evict(mu);
+
+        mu.restarts.borrow_mut().push(restart);
+        let value = mu.apply(thunk, Tag::nil());
+        mu.restarts.borrow_mut().pop();
+
+        fp.value = match value {
+            Ok(value) => value,
+            Err(e) if e.condition == Condition::Unwind && Cons::car(mu, e.tag).eq_(restart) => {
+                Frame {
+                    func,
+                    argv: ProperListIter::new(mu, Cons::cdr(mu, e.tag))
+                        .map(|cons| Cons::car(mu, cons))
+                        .collect(),
+                    value: Tag::nil(),
+                }
+                .apply(mu, func)?
+            }
+            Err(e) => return Err(e),
         };
 
         Ok(())
     }
+
+    fn mu_invoke_restart(mu: &Mu, fp: &mut Frame) -> Result<()> {
+        let name = fp.argv[0];
+        let args = fp.argv[1];
+
+        match Tag::type_of(mu, args) {
+            Type::Cons | Type::Null => {
+                let args: Vec<Tag> = ProperListIter::new(mu, args)
+                    .map(|cons| Cons::car(mu, cons))
+                    .collect();
+
+                Err(Self::invoke_restart(mu, name, &args))
+            }
+            _ => Err(Exception::new(Condition::Type, "mu:invoke-restart", args)),
+        }
+    }
+
+    fn mu_restarts(mu: &Mu, fp: &mut Frame) -> Result<()> {
+        fp.value = Cons::list(mu, &Self::restarts(mu));
+        Ok(())
+    }
+
+    fn mu_with_ex(mu: &Mu, fp: &mut Frame) -> Result<()> {
+        let handler = fp.argv[0];
+        let thunk = fp.argv[1];
+
+        for func in [handler, thunk] {
+            if Tag::type_of(mu, func) != Type::Function {
+                return Err(Exception::new(Condition::Type, "mu:with-ex", func));
+            }
+        }
+
+        fp.value = Self::handler_case(mu, Symbol::keyword("t"), handler, thunk)?;
+        Ok(())
+    }
+
+    fn mu_handler_case(mu: &Mu, fp: &mut Frame) -> Result<()> {
+        let conds = fp.argv[0];
+        let handler = fp.argv[1];
+        let thunk = fp.argv[2];
+
+        match Tag::type_of(mu, conds) {
+            Type::Keyword | Type::Cons => (),
+            _ => return Err(Exception::new(Condition::Type, "mu:handler-case", conds)),
+        }
+
+        for func in [handler, thunk] {
+            if Tag::type_of(mu, func) != Type::Function {
+                return Err(Exception::new(Condition::Type, "mu:handler-case", func));
+            }
+        }
+
+        fp.value = Self::handler_case(mu, conds, handler, thunk)?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::{
+        core::{
+            classes::Tag,
+            compile::Compiler,
+            exception::{Condition, Exception, BACKTRACE_DEPTH},
+            mu::{Core, Mu},
+        },
+        types::{
+            cons::{Cons, Core as _},
+            fixnum::Fixnum,
+            r#struct::Struct,
+            symbol::{Core as _, Symbol},
+            vector::{Core as _, Vector},
+        },
+    };
+
+    fn eval(mu: &Mu, src: &str) -> Tag {
+        let form = mu.read_string(src.to_string()).unwrap();
+
+        mu.eval(<Mu as Compiler>::compile(mu, form).unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn exinfo() {
+        let mu: &Mu = &Core::new("".to_string());
+
+        for (condition, key) in [
+            (Condition::Type, "type"),
+            (Condition::Unwind, "unwind"),
+            (Condition::ZeroDivide, "div0"),
+        ] {
+            let ex = Exception::new(condition, "mu:car", Fixnum::as_tag(1)).to_tag(mu);
+            let exinfo = Struct::vector(mu, ex);
+            let slot = |nth| Vector::r#ref(mu, exinfo, nth).unwrap();
+
+            assert!(Struct::stype(mu, ex).eq_(Symbol::keyword("exinfo")));
+            assert_eq!(Vector::length_of(mu, exinfo), 6);
+            assert!(slot(0).eq_(Symbol::keyword(key)));
+            assert!(slot(1).eq_(Fixnum::as_tag(1)));
+            assert_eq!(Vector::as_string(mu, slot(2)), "mu:car");
+            assert!(slot(3).null_() && slot(4).null_() && slot(5).null_());
+        }
+    }
+
     #[test]
-    fn exception() {
-        assert!(true)
+    fn backtrace() {
+        let mu: &Mu = &Core::new("".to_string());
+
+        eval(
+            mu,
+            "(mu:intern (mu:map-ns \"\") :extern \"deep\" \
+               (:lambda (n) (:if (mu:eq n 0) (mu:car 1) (mu:cons n (deep (mu:fx-sub n 1))))))",
+        );
+
+        let ex = eval(mu, "(mu:with-ex (:lambda (ex) ex) (:lambda () (deep 40)))");
+        let backtrace = Vector::r#ref(mu, Struct::vector(mu, ex), 5).unwrap();
+
+        assert!(Vector::r#ref(mu, Struct::vector(mu, ex), 0)
+            .unwrap()
+            .eq_(Symbol::keyword("type")));
+        assert_eq!(Cons::length(mu, backtrace), BACKTRACE_DEPTH);
     }
 }
@@ -12,17 +12,19 @@ use {
         core::{
             classes::{Tag, Type},
             exception,
-            exception::{Condition, Exception},
+            exception::{Condition, Exception, Resume},
             mu::{Core as _, Mu},
             namespace::Core as _,
+            vm::{Bytecode, TailCall},
         },
+        system::signal::{Core as _, Signal},
         types::{
             cons::{Cons, Core as _, ProperListIter},
             fixnum::Fixnum,
-            function::Function,
+            function::{Arity, Function},
             r#struct::{Core as _, Struct},
             symbol::{Core as _, Symbol},
-            vecimage::{TypedVec, VecType, VectorIter},
+            vecimage::{IVector, IndirectVector, TypedVec, VecType, VectorIter},
             vector::{Core as _, Vector},
         },
     },
@@ -32,14 +34,29 @@ use {
     },
 };
 
+// frame offsets of unsupplied arguments and their default forms
+type Defaults = Vec<(usize, Tag)>;
+
 pub struct Frame {
     pub func: Tag,
     pub argv: Vec<Tag>,
     pub value: Tag,
 }
 
+// a frame captured by a closure keeps its slots in one shared
+// #s(:frame #(func slot ...)) struct, which every holder of the
+// frame reads and writes. a boxed frame on a frame stack holds
+// that struct in value.
 impl Frame {
+    fn boxed(&self) -> Option<Tag> {
+        (!self.value.null_()).then_some(self.value)
+    }
+
     fn to_tag(&self, mu: &Mu) -> Tag {
+        if let Some(boxed) = self.boxed() {
+            return boxed;
+        }
+
         let mut vec: Vec<Tag> = vec![self.func];
 
         for arg in &self.argv {
@@ -49,36 +66,28 @@ impl Frame {
         Struct::new(mu, "frame".to_string(), vec).evict(mu)
     }
 
-    fn from_tag(mu: &Mu, tag: Tag) -> Self {
+    fn from_tag(mu: &Mu, tag: Tag) -> Option<Self> {
         match Tag::type_of(mu, tag) {
             Type::Struct => {
                 let stype = Struct::stype(mu, tag);
                 let frame = Struct::vector(mu, tag);
 
-                let func = Vector::r#ref(mu, frame, 0).unwrap();
-
-                match Tag::type_of(mu, func) {
-                    Type::Function => {
-                        if !stype.eq_(Symbol::keyword("frame")) {
-                            panic!()
-                        }
-
-                        let mut args = Vec::new();
+                if !stype.eq_(Symbol::keyword("frame")) {
+                    return None;
+                }
 
-                        for arg in VectorIter::new(mu, frame).skip(1) {
-                            args.push(arg)
-                        }
+                let func = Vector::r#ref(mu, frame, 0)?;
 
-                        Frame {
-                            func,
-                            argv: args,
-                            value: Tag::nil(),
-                        }
-                    }
-                    _ => panic!(),
+                match Tag::type_of(mu, func) {
+                    Type::Function => Some(Frame {
+                        func,
+                        argv: VectorIter::new(mu, frame).skip(1).collect(),
+                        value: Tag::nil(),
+                    }),
+                    _ => None,
                 }
             }
-            _ => panic!(),
+            _ => None,
         }
     }
 
@@ -116,20 +125,33 @@ impl Frame {
         }
     }
 
-    fn frame_stack_pop(mu: &Mu, id: Tag) {
+    fn frame_stack_pop(mu: &Mu, id: Tag) -> Option<Frame> {
         let stack_ref: Ref<HashMap<u64, RefCell<Vec<Frame>>>> = mu.lexical.borrow();
-        let mut vec_ref: RefMut<Vec<Frame>> = stack_ref[&id.as_u64()].borrow_mut();
+        let mut vec_ref: RefMut<Vec<Frame>> = stack_ref.get(&id.as_u64())?.borrow_mut();
 
-        vec_ref.pop();
+        vec_ref.pop()
     }
 
-    fn frame_stack_ref(mu: &Mu, id: Tag, offset: usize, argv: &mut Vec<u64>) {
+    // a frame popped out from under its activation isn't there to read
+    fn frame_stack_ref(mu: &Mu, id: Tag, offset: usize, argv: &mut Vec<u64>) -> Option<()> {
         let stack_ref: Ref<HashMap<u64, RefCell<Vec<Frame>>>> = mu.lexical.borrow();
-        let vec_ref: Ref<Vec<Frame>> = stack_ref[&id.as_u64()].borrow();
+        let vec_ref: Ref<Vec<Frame>> = stack_ref.get(&id.as_u64())?.borrow();
+        let frame = vec_ref.get(offset)?;
 
-        for value in &vec_ref[offset].argv {
-            argv.push(value.as_u64())
+        match frame.boxed() {
+            Some(boxed) => {
+                for value in VectorIter::new(mu, Struct::vector(mu, boxed)).skip(1) {
+                    argv.push(value.as_u64())
+                }
+            }
+            None => {
+                for value in &frame.argv {
+                    argv.push(value.as_u64())
+                }
+            }
         }
+
+        Some(())
     }
 
     fn frame_stack_len(mu: &Mu, id: Tag) -> Option<usize> {
@@ -144,15 +166,414 @@ impl Frame {
     }
 
     // frame reference
-    fn frame_ref(mu: &Mu, id: u64, offset: usize) -> Option<Tag> {
+    pub fn frame_ref(mu: &Mu, id: u64, offset: usize) -> Option<Tag> {
+        let stack_ref: Ref<HashMap<u64, RefCell<Vec<Frame>>>> = mu.lexical.borrow();
+        let vec_ref: Ref<Vec<Frame>> = stack_ref.get(&id)?.borrow();
+        let frame = vec_ref.last()?;
+
+        match frame.boxed() {
+            Some(boxed) => Vector::r#ref(mu, Struct::vector(mu, boxed), offset + 1),
+            None => frame.argv.get(offset).copied(),
+        }
+    }
+
+    pub fn frame_set(mu: &Mu, id: u64, offset: usize, value: Tag) -> Option<()> {
+        let stack_ref: Ref<HashMap<u64, RefCell<Vec<Frame>>>> = mu.lexical.borrow();
+        let mut vec_ref: RefMut<Vec<Frame>> = stack_ref.get(&id)?.borrow_mut();
+        let frame = vec_ref.last_mut()?;
+
+        match frame.boxed() {
+            Some(boxed) => Self::box_set(mu, boxed, offset, value),
+            None => {
+                *frame.argv.get_mut(offset)? = value;
+                Some(())
+            }
+        }
+    }
+
+    fn box_set(mu: &Mu, boxed: Tag, offset: usize, value: Tag) -> Option<()> {
+        let vector = Struct::vector(mu, boxed);
+
+        if offset + 1 >= Vector::length_of(mu, vector) {
+            return None;
+        }
+
+        IndirectVector::set(mu, vector, offset + 1, value)
+    }
+
+    // let variables extend the frame they're bound in
+    pub fn frame_let(mu: &Mu, id: u64, offset: usize, value: Tag) -> Option<()> {
         let stack_ref: Ref<HashMap<u64, RefCell<Vec<Frame>>>> = mu.lexical.borrow();
-        let vec_ref: Ref<Vec<Frame>> = stack_ref[&id].borrow();
+        let mut vec_ref: RefMut<Vec<Frame>> = stack_ref.get(&id)?.borrow_mut();
+        let frame = vec_ref.last_mut()?;
+
+        if let Some(boxed) = frame.boxed() {
+            return Self::box_set(mu, boxed, offset, value);
+        }
+
+        let argv = &mut frame.argv;
+
+        if argv.len() <= offset {
+            argv.resize(offset + 1, Tag::nil())
+        }
+
+        argv[offset] = value;
+        Some(())
+    }
+
+    // bind the variables of a compiled (:let id offset (init ...) . body),
+    // returns the body
+    pub fn let_body(mu: &Mu, args: Tag) -> exception::Result<Tag> {
+        let id = Fixnum::as_i64(mu, Cons::car(mu, args)) as u64;
+        let offset = Fixnum::as_i64(mu, Cons::nth(mu, 1, args).unwrap()) as usize;
+
+        for (nth, cons) in ProperListIter::new(mu, Cons::nth(mu, 2, args).unwrap()).enumerate() {
+            let value = mu.eval(Cons::car(mu, cons))?;
+
+            mu.clear_values();
+            if Self::frame_let(mu, id, offset + nth, value).is_none() {
+                return Err(Exception::new(
+                    Condition::Type,
+                    "mu::let",
+                    Cons::car(mu, args),
+                ));
+            }
+        }
 
-        Some(vec_ref[vec_ref.len() - 1].argv[offset])
+        Ok(Cons::nthcdr(mu, 3, args).unwrap())
+    }
+
+    // lay out arguments as required, optional, rest, and keys. returns the
+    // frame arguments and the offsets of those waiting on a default form.
+    fn bind_args(
+        mu: &Mu,
+        func: Tag,
+        nreqs: usize,
+        arity: &Arity,
+        args: Vec<Tag>,
+    ) -> exception::Result<(Vec<Tag>, Defaults)> {
+        let arity_error = || Err(Exception::new(Condition::Arity, "frame::apply", func));
+        let nargs = args.len();
+
+        if nargs < nreqs || (!arity.rest && arity.keys.is_empty() && nargs > nreqs + arity.nopt) {
+            return arity_error();
+        }
+
+        let mut argv = args[..nreqs].to_vec();
+        let mut defaults = Vec::new();
+
+        for nth in 0..arity.nopt {
+            match args.get(nreqs + nth) {
+                Some(arg) => argv.push(*arg),
+                None => {
+                    if !arity.defaults[nth].null_() {
+                        defaults.push((argv.len(), arity.defaults[nth]))
+                    }
+                    argv.push(Tag::nil())
+                }
+            }
+        }
+
+        let rest = args.get(nreqs + arity.nopt..).unwrap_or(&[]);
+
+        if arity.rest {
+            argv.push(Cons::list(mu, rest))
+        }
+
+        if !arity.keys.is_empty() {
+            if rest.len() % 2 != 0 {
+                return arity_error();
+            }
+
+            let mut keys = vec![None; arity.keys.len()];
+
+            for pair in rest.chunks(2) {
+                match arity.keys.iter().position(|key| key.eq_(pair[0])) {
+                    Some(nth) => {
+                        if keys[nth].is_none() {
+                            keys[nth] = Some(pair[1])
+                        }
+                    }
+                    None => return arity_error(),
+                }
+            }
+
+            for (nth, key) in keys.into_iter().enumerate() {
+                match key {
+                    Some(value) => argv.push(value),
+                    None => {
+                        let default = arity.defaults[arity.nopt + nth];
+
+                        if !default.null_() {
+                            defaults.push((argv.len(), default))
+                        }
+                        argv.push(Tag::nil())
+                    }
+                }
+            }
+        }
+
+        Ok((argv, defaults))
+    }
+
+    // tail calls
+    fn tail_call(mu: &Mu, expr: Tag) -> exception::Result<TailCall> {
+        if Tag::type_of(mu, expr) != Type::Cons {
+            return Ok(TailCall::Value(mu.eval(expr)?));
+        }
+
+        let func = Cons::car(mu, expr);
+        let args = Cons::cdr(mu, expr);
+
+        // the selected arm of an :if is in tail position
+        if func.eq_(Symbol::keyword("if")) {
+            let arm = if mu.eval(Cons::car(mu, args))?.null_() {
+                2
+            } else {
+                1
+            };
+
+            mu.clear_values();
+            return Self::tail_call(mu, Cons::nth(mu, arm, args).unwrap());
+        }
+
+        // so is the last form of a :let body
+        if func.eq_(Symbol::keyword("let")) {
+            let mut body = ProperListIter::new(mu, Self::let_body(mu, args)?).peekable();
+
+            while let Some(cons) = body.next() {
+                if body.peek().is_none() {
+                    return Self::tail_call(mu, Cons::car(mu, cons));
+                }
+
+                mu.eval(Cons::car(mu, cons))?;
+                mu.clear_values();
+            }
+
+            return Ok(TailCall::Value(Tag::nil()));
+        }
+
+        let func = match Tag::type_of(mu, func) {
+            Type::Symbol => {
+                if Symbol::is_unbound(mu, func) {
+                    return Err(Exception::new(Condition::Unbound, "mu::eval", func));
+                }
+
+                match Tag::type_of(mu, Symbol::value_of(mu, func)) {
+                    Type::Function => Symbol::value_of(mu, func),
+                    _ => return Err(Exception::new(Condition::Type, "mu::eval", func)),
+                }
+            }
+            Type::Function => func,
+            _ => return Ok(TailCall::Value(mu.eval(expr)?)),
+        };
+
+        Signal::poll(mu)?;
+
+        let mut argv = Vec::new();
+        for cons in ProperListIter::new(mu, args) {
+            argv.push(mu.eval(Cons::car(mu, cons))?)
+        }
+
+        // natives don't need the trampoline, and this is the form that called them
+        if Tag::type_of(mu, Function::form_of(mu, func)) != Type::Cons {
+            loop {
+                let frame = Frame {
+                    func,
+                    argv: argv.clone(),
+                    value: Tag::nil(),
+                };
+
+                match frame.apply(mu, func) {
+                    Ok(value) => return Ok(TailCall::Value(value)),
+                    Err(e) => match e.with_form(expr).signal_restartable(mu) {
+                        Resume::Raise(e) => return Err(e),
+                        Resume::Retry => (),
+                        Resume::Value(value) => return Ok(TailCall::Value(value)),
+                    },
+                }
+            }
+        }
+
+        Ok(TailCall::Apply(func, argv))
+    }
+
+    // the innermost depth active frames as a list of (function . #(args)),
+    // outermost first
+    pub fn backtrace(mu: &Mu, depth: usize) -> Tag {
+        let env_ref: Ref<Vec<(u64, usize)>> = mu.dynamic.borrow();
+        let mut frames = Vec::new();
+
+        for (func, offset) in env_ref.iter().skip(env_ref.len().saturating_sub(depth)) {
+            let mut argv = Vec::new();
+
+            if Self::frame_stack_ref(
+                mu,
+                Function::frame_of(mu, Tag::from_u64(*func)),
+                *offset,
+                &mut argv,
+            )
+            .is_none()
+            {
+                continue;
+            }
+            let vec = argv.into_iter().map(Tag::from_u64).collect();
+            let values = TypedVec::<Vec<Tag>> { vec }.vec.to_vector().evict(mu);
+
+            frames.push(Cons::new(Tag::from_u64(*func), values).evict(mu))
+        }
+
+        Cons::list(mu, &frames)
+    }
+
+    // lambda application is a trampoline. a call in tail position
+    // doesn't recurse through eval, it reuses the frame of any earlier
+    // activation of the callee in this trampoline. those activations
+    // have nothing left to do, and frames other functions might still
+    // reference stay put until the trampoline returns.
+    fn apply_lambda(self, mu: &Mu) -> exception::Result<Tag> {
+        let mut active: Vec<(Tag, usize)> = Vec::new();
+        let mut captured: Vec<Tag> = Vec::new();
+        let value = self
+            .trampoline(mu, &mut active, &mut captured)
+            .map_err(|e| e.signal(mu));
+
+        // frames are popped on the way out, exceptions included
+        for id in captured.iter().rev() {
+            Self::frame_stack_pop(mu, *id);
+        }
+
+        for (id, _) in active.iter().rev() {
+            Self::frame_stack_pop(mu, *id);
+            Self::env_pop(mu);
+        }
+
+        value
+    }
+
+    fn trampoline(
+        self,
+        mu: &Mu,
+        active: &mut Vec<(Tag, usize)>,
+        captured: &mut Vec<Tag>,
+    ) -> exception::Result<Tag> {
+        let mut frame = self;
+
+        loop {
+            // the last pass's captured frames went with its body
+            for id in captured.drain(..).rev() {
+                Self::frame_stack_pop(mu, id);
+            }
+
+            let func = frame.func;
+            let id = Function::frame_of(mu, func);
+            let nreqs = Fixnum::as_i64(mu, Function::nreq_of(mu, func)) as usize;
+
+            let defaults = match Arity::from_tag(mu, Function::arity_of(mu, func)) {
+                None if frame.argv.len() == nreqs => Vec::new(),
+                None => return Err(Exception::new(Condition::Arity, "frame::apply", func)),
+                Some(arity) => {
+                    let (argv, defaults) = Self::bind_args(mu, func, nreqs, &arity, frame.argv)?;
+
+                    frame.argv = argv;
+                    defaults
+                }
+            };
+
+            let offset = Self::frame_stack_len(mu, id).unwrap_or(0);
+
+            match active.iter().rposition(|(frame_id, _)| frame_id.eq_(id)) {
+                Some(nth) if active[nth].1 + 1 == offset => {
+                    Self::frame_stack_pop(mu, id);
+                    frame.frame_stack_push(mu);
+                }
+                _ => {
+                    if mu.dynamic.borrow().len() >= mu.max_depth {
+                        return Err(Exception::new(Condition::Stack, "frame::apply", func));
+                    }
+
+                    Self::env_push(mu, func, offset);
+                    frame.frame_stack_push(mu);
+                    active.push((id, offset));
+                }
+            }
+
+            // a closure's captured frames shadow any live ones
+            for cons in ProperListIter::new(mu, Function::env_of(mu, func)) {
+                let env = Cons::car(mu, cons);
+
+                if let Some(mut frame) = Self::from_tag(mu, env) {
+                    captured.push(Function::frame_of(mu, frame.func));
+                    frame.value = env;
+                    frame.frame_stack_push(mu);
+                }
+            }
+
+            // unsupplied arguments see the ones before them
+            for (offset, default) in defaults {
+                let value = mu.eval(default)?;
+
+                Self::frame_set(mu, id.as_u64(), offset, value);
+            }
+
+            mu.clear_values();
+
+            let code = Function::code_of(mu, func);
+            let mut call = if code.null_() {
+                let mut tail = Tag::nil();
+                let mut body = ProperListIter::new(mu, Function::form_of(mu, func)).peekable();
+
+                while let Some(cons) = body.next() {
+                    if body.peek().is_none() {
+                        tail = Cons::car(mu, cons);
+                    } else {
+                        mu.eval(Cons::car(mu, cons))?;
+                        mu.clear_values();
+                    }
+                }
+
+                Self::tail_call(mu, tail)?
+            } else {
+                <Mu as Bytecode>::execute(mu, code)?
+            };
+
+            let next = loop {
+                match call {
+                    TailCall::Apply(callee, argv) => {
+                        if Tag::type_of(mu, Function::form_of(mu, callee)) == Type::Cons {
+                            break TailCall::Apply(callee, argv);
+                        }
+
+                        call = TailCall::Value(
+                            Frame {
+                                func: callee,
+                                argv,
+                                value: Tag::nil(),
+                            }
+                            .apply(mu, callee)?,
+                        );
+                    }
+                    value => break value,
+                }
+            };
+
+            match next {
+                TailCall::Value(value) => return Ok(value),
+                TailCall::Apply(callee, argv) => {
+                    frame = Frame {
+                        func: callee,
+                        argv,
+                        value: Tag::nil(),
+                    };
+                }
+            }
+        }
     }
 
     // apply
     pub fn apply(mut self, mu: &Mu, func: Tag) -> exception::Result<Tag> {
+        Signal::poll(mu)?;
+
         match Tag::type_of(mu, func) {
             Type::Symbol => {
                 if Symbol::is_unbound(mu, func) {
@@ -174,37 +595,15 @@ impl Frame {
                     let fn_off = Fixnum::as_i64(mu, Function::form_of(mu, func)) as usize;
                     let (_, _, _, fnc) = Mu::map_core(fn_off);
 
+                    mu.clear_values();
                     match fnc(mu, &mut self) {
                         Ok(_) => Ok(self.value),
                         Err(e) => Err(e),
                     }
                 }
                 Type::Cons => {
-                    let nreqs = Fixnum::as_i64(mu, Function::nreq_of(mu, func)) as usize;
-                    let nargs = self.argv.len();
-
-                    if nargs != nreqs {
-                        return Err(Exception::new(Condition::Arity, "frame::apply", func));
-                    }
-
-                    let mut value = Tag::nil();
-                    let offset =
-                        Self::frame_stack_len(mu, Function::frame_of(mu, self.func)).unwrap_or(0);
-
-                    Self::env_push(mu, self.func, offset);
-                    self.frame_stack_push(mu);
-
-                    for cons in ProperListIter::new(mu, Function::form_of(mu, func)) {
-                        value = match mu.eval(Cons::car(mu, cons)) {
-                            Ok(value) => value,
-                            Err(e) => return Err(e),
-                        };
-                    }
-
-                    Self::frame_stack_pop(mu, Function::frame_of(mu, func));
-                    Self::env_pop(mu);
-
-                    Ok(value)
+                    self.func = func;
+                    self.apply_lambda(mu)
                 }
                 _ => Err(Exception::new(Condition::Type, "frame::apply::car", func)),
             },
@@ -214,34 +613,74 @@ impl Frame {
 }
 
 pub trait MuFunction {
+    fn mu_closure(_: &Mu, fp: &mut Frame) -> exception::Result<()>;
     fn mu_frames(_: &Mu, fp: &mut Frame) -> exception::Result<()>;
     fn mu_fr_get(_: &Mu, fp: &mut Frame) -> exception::Result<()>;
     fn mu_fr_pop(_: &Mu, fp: &mut Frame) -> exception::Result<()>;
     fn mu_fr_push(_: &Mu, fp: &mut Frame) -> exception::Result<()>;
     fn mu_fr_ref(mu: &Mu, fp: &mut Frame) -> exception::Result<()>;
+    fn mu_fr_setv(mu: &Mu, fp: &mut Frame) -> exception::Result<()>;
 }
 
 impl MuFunction for Frame {
-    fn mu_frames(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
-        let env_ref: Ref<Vec<(u64, usize)>> = mu.dynamic.borrow();
-        let mut frames = Vec::new();
+    fn mu_closure(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let func = fp.argv[0];
 
-        for (func, offset) in env_ref.iter() {
-            let mut argv = Vec::new();
+        if Tag::type_of(mu, func) != Type::Function {
+            return Err(Exception::new(Condition::Type, "mu::closure", func));
+        }
 
-            Self::frame_stack_ref(
-                mu,
-                Function::frame_of(mu, Tag::from_u64(*func)),
-                *offset,
-                &mut argv,
-            );
-            let vec = argv.into_iter().map(Tag::from_u64).collect();
-            let values = TypedVec::<Vec<Tag>> { vec }.vec.to_vector().evict(mu);
+        // the first closure over a frame boxes it, sized to hold every
+        // slot the frame's lambda compiled
+        let mut env = Vec::new();
+        for cons in ProperListIter::new(mu, Function::env_of(mu, func)) {
+            let id = Cons::car(mu, cons);
+            let lexical_ref: Ref<HashMap<u64, RefCell<Vec<Frame>>>> = mu.lexical.borrow();
+
+            let mut stack = match lexical_ref.get(&id.as_u64()) {
+                Some(stack) => stack.borrow_mut(),
+                None => return Err(Exception::new(Condition::Type, "mu::closure", func)),
+            };
+
+            match stack.last_mut() {
+                Some(frame) => {
+                    if frame.value.null_() {
+                        let nslots = match Symbol::value_of(mu, id) {
+                            nslots if Tag::type_of(mu, nslots) == Type::Fixnum => {
+                                Fixnum::as_i64(mu, nslots) as usize
+                            }
+                            _ => 0,
+                        };
 
-            frames.push(Cons::new(Tag::from_u64(*func), values).evict(mu))
+                        if frame.argv.len() < nslots {
+                            frame.argv.resize(nslots, Tag::nil())
+                        }
+
+                        frame.value = frame.to_tag(mu);
+                    }
+
+                    env.push(frame.value)
+                }
+                None => return Err(Exception::new(Condition::Type, "mu::closure", func)),
+            }
         }
 
-        fp.value = Cons::list(mu, &frames);
+        fp.value = Function::new(
+            Function::lambda_of(mu, func),
+            Function::nreq_of(mu, func),
+            Function::form_of(mu, func),
+            Function::frame_of(mu, func),
+            Function::code_of(mu, func),
+            Cons::list(mu, &env),
+            Function::arity_of(mu, func),
+        )
+        .evict(mu);
+
+        Ok(())
+    }
+
+    fn mu_frames(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        fp.value = Self::backtrace(mu, usize::MAX);
         Ok(())
     }
 
@@ -252,9 +691,14 @@ impl MuFunction for Frame {
             Type::Function => {
                 let id = Function::frame_of(mu, func).as_u64();
                 let lexical_ref: Ref<HashMap<u64, RefCell<Vec<Frame>>>> = mu.lexical.borrow();
-                let vec_ref: Ref<Vec<Frame>> = lexical_ref[&id].borrow();
 
-                vec_ref[0].to_tag(mu)
+                match lexical_ref.get(&id) {
+                    Some(stack) => match stack.borrow().first() {
+                        Some(frame) => frame.to_tag(mu),
+                        None => return Err(Exception::new(Condition::Range, "mu:fr-get", func)),
+                    },
+                    None => return Err(Exception::new(Condition::Range, "mu:fr-get", func)),
+                }
             }
             _ => return Err(Exception::new(Condition::Type, "mu:fr-get", func)),
         };
@@ -266,7 +710,11 @@ impl MuFunction for Frame {
         fp.value = fp.argv[0];
 
         match Tag::type_of(mu, fp.value) {
-            Type::Function => Self::frame_stack_pop(mu, fp.value),
+            Type::Function => {
+                if Self::frame_stack_pop(mu, Function::frame_of(mu, fp.value)).is_none() {
+                    return Err(Exception::new(Condition::Range, "mu:fr-pop", fp.value));
+                }
+            }
             _ => return Err(Exception::new(Condition::Type, "mu:fr-pop", fp.value)),
         }
 
@@ -277,7 +725,13 @@ impl MuFunction for Frame {
         fp.value = fp.argv[0];
 
         match Tag::type_of(mu, fp.value) {
-            Type::Vector => Self::from_tag(mu, fp.value).frame_stack_push(mu),
+            Type::Struct => match Self::from_tag(mu, fp.value) {
+                Some(mut frame) => {
+                    frame.value = fp.value;
+                    frame.frame_stack_push(mu)
+                }
+                None => return Err(Exception::new(Condition::Type, "mu:fr-push", fp.value)),
+            },
             _ => return Err(Exception::new(Condition::Type, "mu:fr-push", fp.value)),
         }
 
@@ -306,6 +760,29 @@ impl MuFunction for Frame {
             _ => Err(Exception::new(Condition::Type, "mu:lex-ref", frame)),
         }
     }
+
+    fn mu_fr_setv(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let frame = fp.argv[0];
+        let offset = fp.argv[1];
+
+        fp.value = fp.argv[2];
+
+        match Tag::type_of(mu, frame) {
+            Type::Fixnum => match Tag::type_of(mu, offset) {
+                Type::Fixnum => match Frame::frame_set(
+                    mu,
+                    Fixnum::as_i64(mu, frame) as u64,
+                    Fixnum::as_i64(mu, offset) as usize,
+                    fp.value,
+                ) {
+                    Some(_) => Ok(()),
+                    None => Err(Exception::new(Condition::Range, "mu:fr-setv", offset)),
+                },
+                _ => Err(Exception::new(Condition::Type, "mu:fr-setv", offset)),
+            },
+            _ => Err(Exception::new(Condition::Type, "mu:fr-setv", frame)),
+        }
+    }
 }
 
 #[cfg(test)]
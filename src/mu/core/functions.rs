@@ -5,6 +5,7 @@
 use crate::{
     core::{
         classes::{Tag, Type},
+        compile::Compiler,
         exception,
         exception::{Condition, Exception},
         frame::Frame,
@@ -19,7 +20,7 @@ use crate::{
         namespace::{Core as _, Namespace},
         r#struct::{Core as _, Struct},
         stream::{Core as _, Stream},
-        symbol::{Core as _, Symbol},
+        symbol::{Core as _, Symbol, UNBOUND},
         vector::{Core as _, Vector},
     },
 };
@@ -29,8 +30,12 @@ pub trait MuFunction {
     fn mu_eval(_: &Mu, _: &mut Frame) -> exception::Result<()>;
     fn mu_exit(_: &Mu, _: &mut Frame) -> exception::Result<()>;
     fn mu_apply(_: &Mu, _: &mut Frame) -> exception::Result<()>;
-    fn mu_if(_: &Mu, _: &mut Frame) -> exception::Result<()>;
-    fn mu_write(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn mu_macroexpand_1(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn mu_macroexpand(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn mu_progv(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn mu_values(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn mu_mv_list(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn mu_mv_bind(_: &Mu, _: &mut Frame) -> exception::Result<()>;
     fn mu_view(_: &Mu, _: &mut Frame) -> exception::Result<()>;
     fn mu_tag_of(_: &Mu, _: &mut Frame) -> exception::Result<()>;
     fn mu_fix(_: &Mu, _: &mut Frame) -> exception::Result<()>;
@@ -38,19 +43,27 @@ pub trait MuFunction {
 
 impl MuFunction for Mu {
     fn mu_compile(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
-        fp.value = match mu.compile(fp.argv[0]) {
-            Ok(tag) => tag,
-            Err(e) => return Err(e),
-        };
+        fp.value = mu.compile(fp.argv[0])?;
+
+        Ok(())
+    }
+
+    fn mu_macroexpand_1(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let form = fp.argv[0];
+
+        fp.value = <Mu as Compiler>::macroexpand_1(mu, form)?.unwrap_or(form);
+
+        Ok(())
+    }
+
+    fn mu_macroexpand(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        fp.value = <Mu as Compiler>::macroexpand(mu, fp.argv[0])?;
 
         Ok(())
     }
 
     fn mu_eval(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
-        fp.value = match mu.eval(fp.argv[0]) {
-            Ok(tag) => tag,
-            Err(e) => return Err(e),
-        };
+        fp.value = mu.eval(fp.argv[0])?;
 
         Ok(())
     }
@@ -69,10 +82,7 @@ impl MuFunction for Mu {
                         argv.push(Cons::car(mu, cons))
                     }
 
-                    match (Frame { func, argv, value }).apply(mu, func) {
-                        Ok(value) => value,
-                        Err(e) => return Err(e),
-                    }
+                    (Frame { func, argv, value }).apply(mu, func)?
                 }
                 _ => return Err(Exception::new(Condition::Type, "mu:apply", args)),
             },
@@ -82,39 +92,107 @@ impl MuFunction for Mu {
         Ok(())
     }
 
-    fn mu_write(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
-        let form = fp.argv[0];
-        let escape = fp.argv[1];
-        let stream = fp.argv[2];
+    fn mu_values(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let list = fp.argv[0];
 
-        fp.value = form;
+        fp.value = match Tag::type_of(mu, list) {
+            Type::Null | Type::Cons => mu.set_values(
+                ProperListIter::new(mu, list)
+                    .map(|cons| Cons::car(mu, cons))
+                    .collect(),
+            ),
+            _ => return Err(Exception::new(Condition::Type, "mu:values", list)),
+        };
 
-        match Tag::type_of(mu, stream) {
-            Type::Stream => match mu.write(form, !escape.null_(), stream) {
-                Ok(_) => Ok(()),
-                Err(e) => Err(e),
-            },
-            _ => Err(Exception::new(Condition::Type, "mu:write", stream)),
+        Ok(())
+    }
+
+    fn mu_mv_list(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let thunk = fp.argv[0];
+
+        if Tag::type_of(mu, thunk) != Type::Function {
+            return Err(Exception::new(Condition::Type, "mu::mv-list", thunk));
         }
+
+        let value = mu.apply(thunk, Tag::nil())?;
+
+        fp.value = Cons::list(mu, &mu.values_of(value));
+        Ok(())
     }
 
-    fn mu_if(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
-        let test = fp.argv[0];
-        let true_fn = fp.argv[1];
-        let false_fn = fp.argv[2];
+    // missing values are (), extra values are ignored
+    fn mu_mv_bind(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let func = fp.argv[0];
+        let thunk = fp.argv[1];
 
-        fp.value = match Tag::type_of(mu, true_fn) {
-            Type::Function => match Tag::type_of(mu, false_fn) {
-                Type::Function => {
-                    match mu.apply(if test.null_() { false_fn } else { true_fn }, Tag::nil()) {
-                        Ok(tag) => tag,
-                        Err(e) => return Err(e),
-                    }
-                }
-                _ => return Err(Exception::new(Condition::Type, "mu::if", false_fn)),
-            },
-            _ => return Err(Exception::new(Condition::Type, "mu::if", true_fn)),
-        };
+        for fnc in [func, thunk] {
+            if Tag::type_of(mu, fnc) != Type::Function {
+                return Err(Exception::new(Condition::Type, "mu::mv-bind", fnc));
+            }
+        }
+
+        let value = mu.apply(thunk, Tag::nil())?;
+        let mut argv = mu.values_of(value);
+
+        argv.resize(
+            Fixnum::as_i64(mu, Function::nreq_of(mu, func)) as usize,
+            Tag::nil(),
+        );
+
+        fp.value = Frame {
+            func,
+            argv,
+            value: Tag::nil(),
+        }
+        .apply(mu, func)?;
+
+        Ok(())
+    }
+
+    fn mu_progv(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let symbols = fp.argv[0];
+        let values = fp.argv[1];
+        let thunk = fp.argv[2];
+
+        match Tag::type_of(mu, symbols) {
+            Type::Null | Type::Cons => (),
+            _ => return Err(Exception::new(Condition::Type, "mu::progv", symbols)),
+        }
+
+        match Tag::type_of(mu, values) {
+            Type::Null | Type::Cons => (),
+            _ => return Err(Exception::new(Condition::Type, "mu::progv", values)),
+        }
+
+        if Tag::type_of(mu, thunk) != Type::Function {
+            return Err(Exception::new(Condition::Type, "mu::progv", thunk));
+        }
+
+        for cons in ProperListIter::new(mu, symbols) {
+            let symbol = Cons::car(mu, cons);
+
+            if Tag::type_of(mu, symbol) != Type::Symbol {
+                return Err(Exception::new(Condition::Type, "mu::progv", symbol));
+            }
+        }
+
+        // symbols past the end of values are unbound
+        let mark = mu.bindings.borrow().len();
+        let mut values = ProperListIter::new(mu, values);
+
+        for cons in ProperListIter::new(mu, symbols) {
+            let value = match values.next() {
+                Some(cons) => Cons::car(mu, cons),
+                None => *UNBOUND,
+            };
+
+            mu.bind(Cons::car(mu, cons), value)
+        }
+
+        let value = mu.apply(thunk, Tag::nil());
+
+        mu.unbind(mark);
+        fp.value = value?;
 
         Ok(())
     }
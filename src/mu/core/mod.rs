@@ -12,3 +12,4 @@ pub mod mu; // core API interfaces
 pub mod namespace; // needs to be public for function printing
 pub mod read;
 mod readtable; // needs to be public for type readers
+mod vm;
@@ -7,15 +7,18 @@ use {
     crate::{
         core::{
             classes::{Tag, Type},
-            compile::Compiler,
+            compile::{Compiler, LexicalFrame},
             exception,
-            exception::{Condition, Exception},
+            exception::{Condition, Debugger, Exception, Resume},
             frame::Frame,
             namespace::Core as _,
             read::Reader,
         },
         image::heap::Heap,
-        system::sys as system,
+        system::{
+            signal::{Core as _, Signal},
+            sys as system,
+        },
         types::{
             char::{Char, Core as _},
             cons::{Cons, Core as _, ProperListIter},
@@ -29,30 +32,53 @@ use {
             vector::{Core as _, Vector},
         },
     },
-    std::{cell::RefCell, collections::HashMap},
+    std::{
+        cell::{Cell, RefCell},
+        collections::HashMap,
+    },
 };
 
 // extern
 pub type MuCondition = exception::Condition;
+pub type MuLocation = exception::Location;
 
 // native functions
 pub type MuFunctionType = fn(&Mu, &mut Frame) -> exception::Result<()>;
 
+// compiler warnings
+#[derive(Copy, Clone, PartialEq)]
+pub enum Warn {
+    Off,
+    On,
+    Error,
+}
+
 // mu environment
 pub struct Mu {
     pub version: Tag,
     pub config: String,
+    pub max_depth: usize,
+    pub warnings: Warn,
     pub heap: RefCell<Heap>,
     pub system: system::System,
 
     // environments
-    pub compile: RefCell<Vec<(Tag, Vec<Tag>)>>,
+    pub bindings: RefCell<Vec<(Tag, Tag)>>,
+    pub blocks: RefCell<Vec<(Tag, Tag)>>,
+    pub catches: RefCell<Vec<Tag>>,
+    pub debugger: Cell<Option<Debugger>>,
+    pub handlers: RefCell<Vec<Tag>>,
+    pub restarts: RefCell<Vec<Tag>>,
+    pub compile: RefCell<Vec<LexicalFrame>>,
+    pub undefined: RefCell<Vec<Tag>>,
+    pub values: RefCell<Option<Vec<Tag>>>,
     pub dynamic: RefCell<Vec<(u64, usize)>>,
     pub lexical: RefCell<HashMap<u64, RefCell<Vec<Frame>>>>,
 
     // namespaces
     pub nil_ns: Tag,
     pub mu_ns: Tag,
+    pub sys_ns: Tag,
 
     // standard streams
     pub stdin: Tag,
@@ -74,10 +100,15 @@ pub struct Mu {
 
 pub trait Core {
     const VERSION: &'static str = "0.0.15";
+    const MAX_DEPTH: usize = 10000;
 
     fn new(config: String) -> Self;
     fn apply(&self, _: Tag, _: Tag) -> exception::Result<Tag>;
+    fn catch_interrupt(&self) -> bool;
+    fn clear_interrupt(&self);
     fn eof(&self, _: Tag) -> bool;
+    fn location(&self, _: Tag) -> MuLocation;
+    fn open_file(&self, _: &str, is_input: bool) -> exception::Result<Tag>;
     fn eval(&self, _: Tag) -> exception::Result<Tag>;
     fn eq(&self, _: Tag, _: Tag) -> bool;
     fn nil(&self) -> Tag;
@@ -88,11 +119,179 @@ pub trait Core {
     fn write_string(&self, _: String, _: Tag) -> exception::Result<()>;
 }
 
+impl Mu {
+    // config is name:value[,...]
+    fn config_value(config: &str, name: &str) -> Option<String> {
+        config
+            .split(',')
+            .filter_map(|item| item.split_once(':'))
+            .find(|(key, _)| key.trim() == name)
+            .map(|(_, value)| value.trim().to_string())
+    }
+
+    // the first name:value in config that can't be used, if any
+    pub fn config_error(config: &str) -> Option<String> {
+        if let Some(depth) = Self::config_value(config, "depth") {
+            if depth.parse::<usize>().is_err() {
+                return Some(format!("depth:{depth}"));
+            }
+        }
+
+        match Self::config_value(config, "warn").as_deref() {
+            None | Some("on") | Some("off") | Some("error") => None,
+            Some(warn) => Some(format!("warn:{warn}")),
+        }
+    }
+
+    // compiler warnings print to errout, or raise if warn:error
+    pub fn warn(&self, condition: Condition, source: &str, tag: Tag) -> exception::Result<()> {
+        match self.warnings {
+            Warn::Off => Ok(()),
+            Warn::Error => Err(Exception::new(condition, source, tag)),
+            Warn::On => {
+                self.write_string(format!("warning: {source} "), self.errout)?;
+                self.write(tag, true, self.errout)?;
+                self.write_string("\n".to_string(), self.errout)
+            }
+        }
+    }
+
+    // functions called before they're defined are only reported if
+    // they're still undefined once the top level form or load finishes
+    pub fn warn_undefined(&self) -> exception::Result<()> {
+        let undefined: Vec<Tag> = self.undefined.borrow_mut().drain(..).collect();
+
+        for symbol in undefined {
+            if Symbol::is_unbound(self, symbol) {
+                self.warn(Condition::Unbound, "compile: undefined function", symbol)?
+            }
+        }
+
+        Ok(())
+    }
+
+    // a variable named with a leading underscore is unused on purpose
+    pub fn warn_unused(&self, symbol: Tag) -> exception::Result<()> {
+        if Vector::as_string(self, Symbol::name_of(self, symbol)).starts_with('_') {
+            return Ok(());
+        }
+
+        self.warn(Condition::Unbound, "compile: unused variable", symbol)
+    }
+
+    // multiple values. a native returning more than one value records
+    // them all, and returns the first as its value
+    pub fn set_values(&self, values: Vec<Tag>) -> Tag {
+        let primary = values.first().copied().unwrap_or_else(Tag::nil);
+
+        *self.values.borrow_mut() = Some(values);
+        primary
+    }
+
+    // calls and forms whose value isn't returned forget earlier values
+    pub fn clear_values(&self) {
+        *self.values.borrow_mut() = None
+    }
+
+    // the values of the form that just returned value
+    pub fn values_of(&self, value: Tag) -> Vec<Tag> {
+        match self.values.borrow_mut().take() {
+            Some(values) if values.first().copied().unwrap_or_else(Tag::nil).eq_(value) => values,
+            _ => vec![value],
+        }
+    }
+
+    // dynamic bindings, symbol and the value it shadows
+    pub fn bind(&self, symbol: Tag, value: Tag) {
+        let mut bindings_ref = self.bindings.borrow_mut();
+
+        bindings_ref.push((symbol, Symbol::value_of(self, symbol)));
+        Symbol::set_value(self, symbol, value)
+    }
+
+    // a form that fails where it's evaluated can be retried,
+    // or replaced by a value
+    fn eval_cons(&self, expr: Tag) -> exception::Result<Tag> {
+        Signal::poll(self)?;
+
+        let func = Cons::car(self, expr);
+        let args = Cons::cdr(self, expr);
+        match Tag::type_of(self, func) {
+            Type::Keyword if func.eq_(Symbol::keyword("quote")) => Ok(Cons::car(self, args)),
+            Type::Keyword if func.eq_(Symbol::keyword("if")) => {
+                let arm = if self.eval(Cons::car(self, args))?.null_() {
+                    2
+                } else {
+                    1
+                };
+
+                self.clear_values();
+                self.eval(Cons::nth(self, arm, args).unwrap())
+            }
+            Type::Keyword if func.eq_(Symbol::keyword("let")) => {
+                let mut value = Tag::nil();
+
+                for cons in ProperListIter::new(self, Frame::let_body(self, args)?) {
+                    self.clear_values();
+                    value = self.eval(Cons::car(self, cons))?;
+                }
+
+                Ok(value)
+            }
+            Type::Symbol => {
+                if Symbol::is_unbound(self, func) {
+                    Err(Exception::new(Condition::Unbound, "mu::eval", func))
+                } else {
+                    let fnc = Symbol::value_of(self, func);
+                    match Tag::type_of(self, fnc) {
+                        Type::Function => self.apply(fnc, args),
+                        _ => Err(Exception::new(Condition::Type, "mu::eval", func)),
+                    }
+                }
+            }
+            Type::Function => self.apply(func, args),
+            _ => Err(Exception::new(Condition::Type, "mu::eval", func)),
+        }
+    }
+
+    // restore the bindings made since mark
+    pub fn unbind(&self, mark: usize) {
+        let mut bindings_ref = self.bindings.borrow_mut();
+
+        while bindings_ref.len() > mark {
+            let (symbol, value) = bindings_ref.pop().unwrap();
+
+            Symbol::set_value(self, symbol, value)
+        }
+    }
+}
+
 impl Core for Mu {
     fn new(config: String) -> Self {
+        let max_depth = match Self::config_value(&config, "depth") {
+            Some(depth) => depth.parse::<usize>().unwrap_or(<Mu as Core>::MAX_DEPTH),
+            None => <Mu as Core>::MAX_DEPTH,
+        };
+
+        let warnings = match Self::config_value(&config, "warn").as_deref() {
+            Some("off") => Warn::Off,
+            Some("error") => Warn::Error,
+            _ => Warn::On,
+        };
+
         let mut mu = Mu {
+            bindings: RefCell::new(Vec::new()),
+            blocks: RefCell::new(Vec::new()),
+            catches: RefCell::new(Vec::new()),
+            debugger: Cell::new(None),
+            handlers: RefCell::new(Vec::new()),
+            restarts: RefCell::new(Vec::new()),
             compile: RefCell::new(Vec::new()),
+            undefined: RefCell::new(Vec::new()),
+            values: RefCell::new(None),
             config,
+            max_depth,
+            warnings,
             dynamic: RefCell::new(Vec::new()),
             errout: Tag::nil(),
             heap: RefCell::new(Heap::new(1024)),
@@ -102,6 +301,7 @@ impl Core for Mu {
             ns_caches: RefCell::new(HashMap::new()),
             stdin: Tag::nil(),
             stdout: Tag::nil(),
+            sys_ns: Tag::nil(),
             system: system::System::new(),
             version: Tag::nil(),
         };
@@ -125,6 +325,7 @@ impl Core for Mu {
 
         mu.nil_ns = Namespace::new(&mu, "", Tag::nil()).evict(&mu);
         mu.mu_ns = Namespace::new(&mu, "mu", Tag::nil()).evict(&mu);
+        mu.sys_ns = Namespace::new(&mu, "system", Tag::nil()).evict(&mu);
 
         match Namespace::add_ns(&mu, mu.nil_ns) {
             Ok(_) => (),
@@ -136,6 +337,11 @@ impl Core for Mu {
             Err(_) => panic!(),
         };
 
+        match Namespace::add_ns(&mu, mu.sys_ns) {
+            Ok(_) => (),
+            Err(_) => panic!(),
+        };
+
         Self::install_mu_symbols(&mu);
 
         mu
@@ -159,34 +365,31 @@ impl Core for Mu {
         Frame { func, argv, value }.apply(self, func)
     }
 
+    // SIGINT raises :intr at the next eval or apply
+    fn catch_interrupt(&self) -> bool {
+        Signal::catch(libc::SIGINT)
+    }
+
+    fn clear_interrupt(&self) {
+        Signal::clear(libc::SIGINT)
+    }
+
     fn eq(&self, tag: Tag, tag1: Tag) -> bool {
         tag.eq_(tag1)
     }
 
     fn eval(&self, expr: Tag) -> exception::Result<Tag> {
         match Tag::type_of(self, expr) {
-            Type::Cons => {
-                let func = Cons::car(self, expr);
-                let args = Cons::cdr(self, expr);
-                match Tag::type_of(self, func) {
-                    Type::Keyword if func.eq_(Symbol::keyword("quote")) => {
-                        Ok(Cons::car(self, args))
-                    }
-                    Type::Symbol => {
-                        if Symbol::is_unbound(self, func) {
-                            Err(Exception::new(Condition::Unbound, "mu::eval", func))
-                        } else {
-                            let fnc = Symbol::value_of(self, func);
-                            match Tag::type_of(self, fnc) {
-                                Type::Function => self.apply(fnc, args),
-                                _ => Err(Exception::new(Condition::Type, "mu::eval", func)),
-                            }
-                        }
-                    }
-                    Type::Function => self.apply(func, args),
-                    _ => Err(Exception::new(Condition::Type, "mu::eval", func)),
+            Type::Cons => loop {
+                match self.eval_cons(expr) {
+                    Ok(value) => break Ok(value),
+                    Err(e) => match e.with_form(expr).signal_restartable(self) {
+                        Resume::Raise(e) => break Err(e),
+                        Resume::Retry => (),
+                        Resume::Value(value) => break Ok(value),
+                    },
                 }
-            }
+            },
             Type::Symbol => {
                 if Symbol::is_unbound(self, expr) {
                     Err(Exception::new(Condition::Unbound, "mu:eval", expr))
@@ -206,8 +409,22 @@ impl Core for Mu {
         Stream::is_eof(self, stream)
     }
 
+    fn location(&self, stream: Tag) -> MuLocation {
+        Stream::location(self, stream)
+    }
+
     fn read(&self, stream: Tag, eofp: bool, eof_value: Tag) -> exception::Result<Tag> {
-        <Mu as Reader>::read(self, stream, eofp, eof_value, false)
+        <Mu as Reader>::read(self, stream, eofp, eof_value, false).map_err(|mut e| {
+            if e.location.is_none() {
+                e.location = Some(Box::new(Stream::location(self, stream)));
+            }
+
+            e
+        })
+    }
+
+    fn open_file(&self, path: &str, is_input: bool) -> exception::Result<Tag> {
+        Stream::open_file(self, path, is_input)
     }
 
     fn read_string(&self, string: String) -> exception::Result<Tag> {
@@ -11,6 +11,7 @@ use crate::{
         image::MuFunction as _,
         mu::{Mu, MuFunctionType},
     },
+    system::sys::{MuFunction as _, System},
     types::{
         coerce::MuFunction as _,
         cons::{Cons, MuFunction as _},
@@ -43,16 +44,26 @@ lazy_static! {
         ("exit", Scope::Intern, 1, Mu::mu_exit),
         ("fix", Scope::Extern, 2, Mu::mu_fix),
         ("hp-info", Scope::Extern, 0, Mu::mu_hp_info),
+        ("macroexpand", Scope::Extern, 1, Mu::mu_macroexpand),
+        ("macroexpand-1", Scope::Extern, 1, Mu::mu_macroexpand_1),
         ("tag-of", Scope::Extern, 1, Mu::mu_tag_of),
+        ("values", Scope::Extern, 1, Mu::mu_values),
         ("view", Scope::Extern, 1, Mu::mu_view),
         // exceptions
         ("with-ex", Scope::Extern, 2, Exception::mu_with_ex),
         ("raise", Scope::Extern, 2, Exception::mu_raise),
+        ("throw", Scope::Extern, 2, Exception::mu_throw),
+        ("handler-bind", Scope::Extern, 2, Exception::mu_handler_bind),
+        ("handler-case", Scope::Extern, 3, Exception::mu_handler_case),
+        ("with-restart", Scope::Extern, 3, Exception::mu_with_restart),
+        ("invoke-restart", Scope::Extern, 2, Exception::mu_invoke_restart),
+        ("restarts", Scope::Extern, 0, Exception::mu_restarts),
         // frames
         ("frames", Scope::Intern, 0, Frame::mu_frames),
         ("fr-get", Scope::Extern, 1, Frame::mu_fr_get),
         ("fr-pop", Scope::Extern, 1, Frame::mu_fr_pop),
         ("fr-push", Scope::Extern, 1, Frame::mu_fr_push),
+        ("fr-setv", Scope::Extern, 3, Frame::mu_fr_setv),
         // types
         ("eq", Scope::Extern, 2, Tag::mu_eq),
         ("type-of", Scope::Extern, 1, Tag::mu_typeof),
@@ -101,26 +112,64 @@ lazy_static! {
         ("st-type", Scope::Extern, 1, Struct::mu_struct_type),
         ("st-vec", Scope::Extern, 1, Struct::mu_struct_vector),
         // streams
+        ("accept", Scope::Extern, 1, Stream::mu_accept),
         ("close", Scope::Extern, 1, Stream::mu_close),
         ("eof", Scope::Extern, 1, Stream::mu_eof),
         ("flush", Scope::Extern, 1, Stream::mu_flush),
+        ("get-bytes", Scope::Extern, 1, Stream::mu_get_bytes),
         ("get-str", Scope::Extern, 1, Stream::mu_get_string),
         ("open", Scope::Extern, 3, Stream::mu_open),
         ("openp", Scope::Extern, 1, Stream::mu_openp),
         ("rd-byte", Scope::Extern, 3, Stream::mu_read_byte),
+        ("rd-bytes", Scope::Extern, 2, Stream::mu_read_bytes),
         ("rd-char", Scope::Extern, 3, Stream::mu_read_char),
+        ("stream-loc", Scope::Extern, 1, Stream::mu_stream_loc),
         ("un-char", Scope::Extern, 2, Stream::mu_unread_char),
         ("wr-byte", Scope::Extern, 2, Stream::mu_write_byte),
+        ("wr-bytes", Scope::Extern, 2, Stream::mu_write_bytes),
         ("wr-char", Scope::Extern, 2, Stream::mu_write_char),
         // interns
-        ("if", Scope::Intern, 3, Mu::mu_if),
+        ("catch", Scope::Intern, 2, Exception::mu_catch),
+        ("closure", Scope::Intern, 1, Frame::mu_closure),
+        ("mv-bind", Scope::Intern, 2, Mu::mu_mv_bind),
+        ("mv-list", Scope::Intern, 1, Mu::mu_mv_list),
+        ("progv", Scope::Intern, 3, Mu::mu_progv),
+        ("protect", Scope::Intern, 2, Exception::mu_protect),
         ("fr-ref", Scope::Intern, 2, Frame::mu_fr_ref),
     ];
+
+    // system namespace, dispatched past the end of FUNCTIONMAP
+    static ref SYSTEMMAP: Vec<<Mu as Core>::FunctionDesc> = vec![
+        ("chdir", Scope::Intern, 1, System::sys_chdir),
+        ("clock", Scope::Extern, 0, System::sys_clock),
+        ("cwd", Scope::Extern, 0, System::sys_cwd),
+        ("exists", Scope::Extern, 1, System::sys_exists),
+        ("exit", Scope::Extern, 1, System::sys_exit),
+        ("getenv", Scope::Extern, 1, System::sys_getenv),
+        ("logname", Scope::Extern, 0, System::sys_logname),
+        ("mkdir", Scope::Extern, 1, System::sys_mkdir),
+        ("pid", Scope::Extern, 0, System::sys_pid),
+        ("procenv", Scope::Extern, 0, System::sys_procenv),
+        ("readdir", Scope::Extern, 1, System::sys_readdir),
+        ("realpath", Scope::Extern, 1, System::sys_realpath),
+        ("rename", Scope::Extern, 2, System::sys_rename),
+        ("rmdir", Scope::Extern, 1, System::sys_rmdir),
+        ("setenv", Scope::Extern, 2, System::sys_setenv),
+        ("signal", Scope::Extern, 2, System::sys_signal),
+        ("sleep", Scope::Extern, 1, System::sys_sleep),
+        ("stat", Scope::Extern, 1, System::sys_stat),
+        ("system", Scope::Extern, 1, System::sys_system),
+        ("time", Scope::Extern, 0, System::sys_time),
+        ("uid", Scope::Extern, 0, System::sys_uid),
+        ("unlink", Scope::Extern, 1, System::sys_unlink),
+        ("utimes", Scope::Extern, 0, System::sys_utimes),
+    ];
 }
 
 pub trait Core {
     type FunctionDesc;
     fn map_core(_: usize) -> <Mu as Core>::FunctionDesc;
+    fn ncore() -> usize;
     fn install_mu_symbols(_: &Mu);
 }
 
@@ -128,7 +177,15 @@ impl Core for Mu {
     type FunctionDesc = (&'static str, Scope, u16, MuFunctionType);
 
     fn map_core(index: usize) -> <Mu as Core>::FunctionDesc {
-        FUNCTIONMAP[index]
+        if index < FUNCTIONMAP.len() {
+            FUNCTIONMAP[index]
+        } else {
+            SYSTEMMAP[index - FUNCTIONMAP.len()]
+        }
+    }
+
+    fn ncore() -> usize {
+        FUNCTIONMAP.len() + SYSTEMMAP.len()
     }
 
     fn install_mu_symbols(mu: &Mu) {
@@ -155,29 +212,163 @@ impl Core for Mu {
             mu.errout,
         );
 
-        for (id, fnmap) in FUNCTIONMAP.iter().enumerate() {
+        let namespaces = FUNCTIONMAP
+            .iter()
+            .map(|fnmap| (mu.mu_ns, fnmap))
+            .chain(SYSTEMMAP.iter().map(|fnmap| (mu.sys_ns, fnmap)));
+
+        for (id, (ns, fnmap)) in namespaces.enumerate() {
             let (name, scope, nreqs, _) = fnmap;
 
             let func = Function::new(
                 Tag::nil(),
                 Fixnum::as_tag(*nreqs as i64),
-                Fixnum::as_tag(match id.try_into().unwrap() {
+                Fixnum::as_tag(match id.into() {
                     Some(n) => n as i64,
                     None => panic!(),
                 }),
                 Tag::nil(),
+                Tag::nil(),
+                Tag::nil(),
+                Tag::nil(),
             )
             .evict(mu);
 
-            Namespace::intern(mu, mu.mu_ns, *scope, name.to_string(), func);
+            Namespace::intern(mu, ns, *scope, name.to_string(), func);
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use {
+        super::{FUNCTIONMAP, SYSTEMMAP},
+        crate::{
+            core::{
+                classes::{Tag, Type},
+                frame::Frame,
+                mu::{Core as _, Mu},
+            },
+            system::sys::{MuFunction as _, System},
+            types::{
+                fixnum::Fixnum,
+                stream::{Core as _, Stream},
+                vector::{Core as _, Vector},
+            },
+        },
+        std::{
+            env, fs,
+            panic::{self, AssertUnwindSafe},
+            process,
+        },
+    };
+
     #[test]
     fn namespace() {
         assert_eq!(2 + 2, 4);
     }
+
+    // one of everything, made fresh for every call. the string is a
+    // path in a scratch directory, and names a scratch env variable
+    fn samples(mu: &Mu, scratch: &str) -> Vec<Tag> {
+        let mut samples: Vec<Tag> = [
+            "#\\a",
+            "'(1 2)",
+            "0",
+            "1",
+            "-1",
+            "2305843009213693951",
+            "-2305843009213693952",
+            "1.0",
+            "mu:car",
+            ":a",
+            "()",
+            "'#s(:a 1)",
+            "'mu:version",
+            "'#(:t 1 2)",
+            "'#(:byte 1 2)",
+            "(:lambda (x) x)",
+        ]
+        .iter()
+        .map(|src| {
+            let form = mu.read_string(src.to_string()).unwrap();
+
+            mu.eval(mu.compile(form).unwrap()).unwrap()
+        })
+        .collect();
+
+        samples.push(Vector::from_string(scratch).evict(mu));
+        samples.push(Stream::open_string(mu, "abc", true).unwrap());
+        samples.push(Stream::open_string(mu, "", false).unwrap());
+        samples.push(mu.mu_ns);
+        samples
+    }
+
+    // every native, called with every kind of argument, returns
+    // a value or an exception
+    #[test]
+    fn natives_dont_panic() {
+        let mu: &Mu = &<Mu as crate::core::mu::Core>::new("".to_string());
+        let dir = env::temp_dir().join(format!("mu-natives-{}", process::id()));
+        let scratch = dir.join("abc").to_string_lossy().to_string();
+        let cwd = env::current_dir().unwrap();
+        let nsamples = samples(mu, &scratch).len();
+        let mut panics = Vec::new();
+
+        fs::create_dir_all(&dir).unwrap();
+
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(|_| ()));
+        for (name, _, nreqs, native) in FUNCTIONMAP.iter().chain(SYSTEMMAP.iter()) {
+            // exit doesn't return
+            if *name == "exit" {
+                continue;
+            }
+
+            let mut samples = samples(mu, &scratch);
+
+            // sleep for no time at all
+            if *name == "sleep" {
+                for sample in samples.iter_mut() {
+                    if Tag::type_of(mu, *sample) == Type::Fixnum {
+                        *sample = Fixnum::as_tag(0)
+                    }
+                }
+            }
+
+            for mut nth in 0..nsamples.pow(*nreqs as u32) {
+                let mut argv = Vec::new();
+
+                for _ in 0..*nreqs {
+                    argv.push(samples[nth % nsamples]);
+                    nth /= nsamples;
+                }
+
+                let mut fp = Frame {
+                    func: Tag::nil(),
+                    argv,
+                    value: Tag::nil(),
+                };
+
+                if panic::catch_unwind(AssertUnwindSafe(|| native(mu, &mut fp))).is_err() {
+                    panics.push(name.to_string())
+                }
+            }
+        }
+        panic::set_hook(hook);
+
+        // put back what the system natives changed
+        let mut fp = Frame {
+            func: Tag::nil(),
+            argv: vec![Fixnum::as_tag(1), Tag::nil()],
+            value: Tag::nil(),
+        };
+        let _ = System::sys_signal(mu, &mut fp);
+        env::set_current_dir(cwd).unwrap();
+        env::remove_var(&scratch);
+        let _ = fs::remove_dir_all(&dir);
+
+        panics.dedup();
+        assert!(panics.is_empty(), "{panics:?}")
+    }
 }
@@ -296,7 +296,7 @@ impl Reader for Mu {
                 Ok(Some(ch_)) => match map_char_syntax(ch_) {
                     Some(stype) => match stype {
                         SyntaxType::Constituent => {
-                            Stream::unread_char(mu, stream, ch_).unwrap();
+                            Stream::unread_char(mu, stream, ch_)?;
                             match Self::read_token(mu, stream) {
                                 Ok(Some(str)) => {
                                     let phrase = ch.to_string() + &str;
@@ -306,14 +306,11 @@ impl Reader for Mu {
                                         "space" => Ok(Some(Char::as_tag(' '))),
                                         "page" => Ok(Some(Char::as_tag('\x0c'))),
                                         "return" => Ok(Some(Char::as_tag('\r'))),
-                                        _ => {
-                                            println!("erad_char_literal: {str}");
-                                            Err(Exception::new(
-                                                Condition::Range,
-                                                "read::read_char_literal",
-                                                stream,
-                                            ))
-                                        }
+                                        _ => Err(Exception::new(
+                                            Condition::Range,
+                                            "read::read_char_literal",
+                                            stream,
+                                        )),
                                     }
                                 }
                                 Ok(None) => Err(Exception::new(
@@ -0,0 +1,537 @@
+//  SPDX-FileCopyrightText: Copyright 2022 James M. Putnam (putnamjm.design@gmail.com)
+//  SPDX-License-Identifier: MIT
+
+//! lambda bytecode
+//!    assemble
+//!    execute
+use crate::{
+    core::{
+        classes::{Tag, Type},
+        exception,
+        exception::{Condition, Exception, Resume},
+        frame::Frame,
+        mu::Mu,
+        namespace::Core as _,
+    },
+    system::signal::{Core as _, Signal},
+    types::{
+        cons::{Cons, Core as _, ProperListIter},
+        fixnum::Fixnum,
+        function::Function,
+        symbol::{Core as _, Symbol},
+        vecimage::{TypedVec, VecType},
+        vector::{Core as _, Vector},
+    },
+};
+
+// opcodes, operands are little-endian
+const CONST: u8 = 0; // tag:8           push tag
+const SYMBOL: u8 = 1; // symbol:8       push symbol value
+const FUNCTION: u8 = 2; // symbol:8     push symbol function value
+const FRAME_REF: u8 = 3; // id:8 nth:4  push frame argument
+const NATIVE: u8 = 4; // index:4 nargs:1 form:8 call native by FUNCTIONMAP index
+const CALL: u8 = 5; // nargs:1          call function under arguments
+const TAIL_CALL: u8 = 6; // nargs:1     return the call to the trampoline
+const BRANCH: u8 = 7; // pc:4           pop, branch if nil
+const JUMP: u8 = 8; // pc:4
+const POP: u8 = 9;
+const RETURN: u8 = 10; //               return top of stack
+const FRAME_SET: u8 = 11; // id:8 nth:4 pop into frame argument
+
+// the form in tail position of a lambda body
+pub enum TailCall {
+    Apply(Tag, Vec<Tag>),
+    Value(Tag),
+}
+
+struct Assembler<'a> {
+    mu: &'a Mu,
+    code: Vec<u8>,
+}
+
+impl<'a> Assembler<'a> {
+    fn emit(&mut self, op: u8, operand: &[u8]) {
+        self.code.push(op);
+        self.code.extend_from_slice(operand);
+    }
+
+    fn emit_tag(&mut self, op: u8, tag: Tag) {
+        self.emit(op, &tag.as_u64().to_le_bytes())
+    }
+
+    // the native a symbol names in a kernel namespace, if any
+    fn native(&self, func: Tag) -> Option<(usize, u16)> {
+        let mu = self.mu;
+
+        if Tag::type_of(mu, func) != Type::Symbol || Symbol::is_unbound(mu, func) {
+            return None;
+        }
+
+        let ns = Symbol::namespace_of(mu, func);
+        if !ns.eq_(mu.mu_ns) && !ns.eq_(mu.sys_ns) {
+            return None;
+        }
+
+        let value = Symbol::value_of(mu, func);
+        if Tag::type_of(mu, value) != Type::Function {
+            return None;
+        }
+
+        let form = Function::form_of(mu, value);
+        match Tag::type_of(mu, form) {
+            Type::Fixnum => {
+                let index = Fixnum::as_i64(mu, form) as usize;
+                let (_, _, nreqs, _) = Mu::map_core(index);
+
+                Some((index, nreqs))
+            }
+            _ => None,
+        }
+    }
+
+    fn body(&mut self, body: Tag, tail: bool) -> Option<()> {
+        let mu = self.mu;
+
+        if body.null_() {
+            self.emit_tag(CONST, Tag::nil());
+            return Some(());
+        }
+
+        let mut forms = ProperListIter::new(mu, body).peekable();
+
+        while let Some(cons) = forms.next() {
+            let last = forms.peek().is_none();
+
+            self.form(Cons::car(mu, cons), tail && last)?;
+            if !last {
+                self.emit(POP, &[]);
+            }
+        }
+
+        Some(())
+    }
+
+    fn args(&mut self, args: Tag) -> Option<u8> {
+        let mu = self.mu;
+        let mut nargs = 0usize;
+
+        for cons in ProperListIter::new(mu, args) {
+            self.form(Cons::car(mu, cons), false)?;
+            nargs += 1;
+        }
+
+        u8::try_from(nargs).ok()
+    }
+
+    fn patch(&mut self, at: usize) {
+        let pc = (self.code.len() as u32).to_le_bytes();
+
+        self.code[at..at + 4].copy_from_slice(&pc);
+    }
+
+    fn form(&mut self, expr: Tag, tail: bool) -> Option<()> {
+        let mu = self.mu;
+
+        match Tag::type_of(mu, expr) {
+            Type::Symbol => self.emit_tag(SYMBOL, expr),
+            Type::Cons => {
+                let func = Cons::car(mu, expr);
+                let args = Cons::cdr(mu, expr);
+                let nargs = Cons::length(mu, args);
+
+                match Tag::type_of(mu, func) {
+                    Type::Keyword if func.eq_(Symbol::keyword("quote")) => {
+                        self.emit_tag(CONST, Cons::car(mu, args))
+                    }
+                    Type::Keyword if func.eq_(Symbol::keyword("let")) => {
+                        let id = Fixnum::as_i64(mu, Cons::car(mu, args)) as u64;
+                        let offset = Fixnum::as_i64(mu, Cons::nth(mu, 1, args).unwrap()) as u32;
+
+                        for (nth, cons) in
+                            ProperListIter::new(mu, Cons::nth(mu, 2, args).unwrap()).enumerate()
+                        {
+                            self.form(Cons::car(mu, cons), false)?;
+
+                            let mut operand = id.to_le_bytes().to_vec();
+                            operand.extend_from_slice(&(offset + nth as u32).to_le_bytes());
+                            self.emit(FRAME_SET, &operand);
+                        }
+
+                        self.body(Cons::nthcdr(mu, 3, args).unwrap(), tail)?;
+                    }
+                    Type::Keyword if func.eq_(Symbol::keyword("if")) => {
+                        self.form(Cons::car(mu, args), false)?;
+                        self.emit(BRANCH, &[0; 4]);
+                        let branch = self.code.len() - 4;
+
+                        self.form(Cons::nth(mu, 1, args).unwrap(), tail)?;
+                        self.emit(JUMP, &[0; 4]);
+                        let jump = self.code.len() - 4;
+
+                        self.patch(branch);
+                        self.form(Cons::nth(mu, 2, args).unwrap(), tail)?;
+                        self.patch(jump);
+                    }
+                    Type::Keyword => return None,
+                    _ => match self.native(func) {
+                        Some((index, nreqs)) if nreqs as usize == nargs => {
+                            let (name, _, _, _) = Mu::map_core(index);
+                            let argv: Vec<Tag> = ProperListIter::new(mu, args)
+                                .map(|cons| Cons::car(mu, cons))
+                                .collect();
+
+                            if name == "fr-ref"
+                                && argv
+                                    .iter()
+                                    .all(|arg| Tag::type_of(mu, *arg) == Type::Fixnum)
+                            {
+                                let mut operand =
+                                    (Fixnum::as_i64(mu, argv[0]) as u64).to_le_bytes().to_vec();
+
+                                operand.extend_from_slice(
+                                    &(Fixnum::as_i64(mu, argv[1]) as u32).to_le_bytes(),
+                                );
+                                self.emit(FRAME_REF, &operand);
+                            } else {
+                                let nargs = self.args(args)?;
+
+                                let mut operand = (index as u32).to_le_bytes().to_vec();
+                                operand.push(nargs);
+                                operand.extend_from_slice(&expr.as_u64().to_le_bytes());
+                                self.emit(NATIVE, &operand);
+                            }
+                        }
+                        _ => {
+                            match Tag::type_of(mu, func) {
+                                Type::Symbol => self.emit_tag(FUNCTION, func),
+                                Type::Function => self.emit_tag(CONST, func),
+                                _ => return None,
+                            }
+
+                            let nargs = self.args(args)?;
+                            self.emit(if tail { TAIL_CALL } else { CALL }, &[nargs]);
+                        }
+                    },
+                }
+            }
+            _ => self.emit_tag(CONST, expr),
+        }
+
+        Some(())
+    }
+}
+
+struct Machine<'a> {
+    code: &'a [u8],
+    pc: usize,
+    stack: Vec<Tag>,
+    tag: Tag,
+}
+
+// a malformed code vector raises rather than panics
+impl<'a> Machine<'a> {
+    fn fault(&self) -> Exception {
+        Exception::new(Condition::Range, "vm::execute", self.tag)
+    }
+
+    fn operand(&mut self, len: usize) -> exception::Result<&'a [u8]> {
+        let code = self.code;
+
+        match code.get(self.pc..self.pc + len) {
+            Some(bytes) => {
+                self.pc += len;
+                Ok(bytes)
+            }
+            None => Err(self.fault()),
+        }
+    }
+
+    fn u8(&mut self) -> exception::Result<u8> {
+        Ok(self.operand(1)?[0])
+    }
+
+    fn u32(&mut self) -> exception::Result<u32> {
+        let mut bytes = [0u8; 4];
+
+        bytes.copy_from_slice(self.operand(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn u64(&mut self) -> exception::Result<u64> {
+        let mut bytes = [0u8; 8];
+
+        bytes.copy_from_slice(self.operand(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn pop(&mut self) -> exception::Result<Tag> {
+        match self.stack.pop() {
+            Some(tag) => Ok(tag),
+            None => Err(self.fault()),
+        }
+    }
+
+    fn pop_n(&mut self, nargs: usize) -> exception::Result<Vec<Tag>> {
+        if nargs > self.stack.len() {
+            return Err(self.fault());
+        }
+
+        Ok(self.stack.split_off(self.stack.len() - nargs))
+    }
+}
+
+pub trait Bytecode {
+    fn assemble(_: &Mu, _: Tag) -> Tag;
+    fn execute(_: &Mu, _: Tag) -> exception::Result<TailCall>;
+}
+
+impl Bytecode for Mu {
+    // assemble a compiled lambda body, () if the body uses
+    // anything the machine doesn't know
+    fn assemble(mu: &Mu, body: Tag) -> Tag {
+        let mut asm = Assembler {
+            mu,
+            code: Vec::new(),
+        };
+
+        match asm.body(body, true) {
+            Some(_) => {
+                asm.emit(RETURN, &[]);
+                TypedVec::<Vec<u8>> { vec: asm.code }
+                    .vec
+                    .to_vector()
+                    .evict(mu)
+            }
+            None => Tag::nil(),
+        }
+    }
+
+    fn execute(mu: &Mu, code: Tag) -> exception::Result<TailCall> {
+        match Tag::type_of(mu, code) {
+            Type::Vector if Vector::type_of(mu, code) == Type::Byte => (),
+            _ => return Err(Exception::new(Condition::Type, "vm::execute", code)),
+        }
+
+        let bytes = Vector::as_bytes(mu, code);
+        let mut vm = Machine {
+            code: &bytes,
+            pc: 0,
+            stack: Vec::new(),
+            tag: code,
+        };
+
+        loop {
+            match vm.u8()? {
+                CONST => {
+                    let tag = Tag::from_u64(vm.u64()?);
+
+                    vm.stack.push(tag)
+                }
+                SYMBOL => {
+                    let symbol = Tag::from_u64(vm.u64()?);
+
+                    if Symbol::is_unbound(mu, symbol) {
+                        return Err(Exception::new(Condition::Unbound, "mu:eval", symbol));
+                    }
+
+                    vm.stack.push(Symbol::value_of(mu, symbol))
+                }
+                FUNCTION => {
+                    let symbol = Tag::from_u64(vm.u64()?);
+
+                    if Symbol::is_unbound(mu, symbol) {
+                        return Err(Exception::new(Condition::Unbound, "mu::eval", symbol));
+                    }
+
+                    let func = Symbol::value_of(mu, symbol);
+                    if Tag::type_of(mu, func) != Type::Function {
+                        return Err(Exception::new(Condition::Type, "mu::eval", symbol));
+                    }
+
+                    vm.stack.push(func)
+                }
+                FRAME_REF => {
+                    let id = vm.u64()?;
+                    let nth = vm.u32()? as usize;
+
+                    match Frame::frame_ref(mu, id, nth) {
+                        Some(value) => vm.stack.push(value),
+                        None => {
+                            return Err(Exception::new(
+                                Condition::Type,
+                                "mu:lex-ref",
+                                Fixnum::as_tag(id as i64),
+                            ))
+                        }
+                    }
+                }
+                NATIVE => {
+                    let index = vm.u32()? as usize;
+                    let nargs = vm.u8()? as usize;
+                    let form = Tag::from_u64(vm.u64()?);
+
+                    if index >= Mu::ncore() {
+                        return Err(vm.fault());
+                    }
+
+                    let (_, _, nreqs, native) = Mu::map_core(index);
+
+                    if nreqs as usize != nargs {
+                        return Err(vm.fault());
+                    }
+
+                    Signal::poll(mu)?;
+
+                    let mut fp = Frame {
+                        func: Tag::nil(),
+                        argv: vm.pop_n(nargs)?,
+                        value: Tag::nil(),
+                    };
+
+                    let value = loop {
+                        mu.clear_values();
+                        match native(mu, &mut fp) {
+                            Ok(_) => break fp.value,
+                            Err(e) => match e.with_form(form).signal_restartable(mu) {
+                                Resume::Raise(e) => return Err(e),
+                                Resume::Retry => (),
+                                Resume::Value(value) => break value,
+                            },
+                        }
+                    };
+
+                    vm.stack.push(value)
+                }
+                CALL => {
+                    let nargs = vm.u8()? as usize;
+                    let argv = vm.pop_n(nargs)?;
+                    let func = vm.pop()?;
+                    let value = Frame {
+                        func,
+                        argv,
+                        value: Tag::nil(),
+                    }
+                    .apply(mu, func)?;
+
+                    vm.stack.push(value)
+                }
+                TAIL_CALL => {
+                    let nargs = vm.u8()? as usize;
+                    let argv = vm.pop_n(nargs)?;
+                    let func = vm.pop()?;
+
+                    return Ok(TailCall::Apply(func, argv));
+                }
+                BRANCH => {
+                    let pc = vm.u32()? as usize;
+
+                    mu.clear_values();
+                    if vm.pop()?.null_() {
+                        vm.pc = pc
+                    }
+                }
+                JUMP => vm.pc = vm.u32()? as usize,
+                POP => {
+                    mu.clear_values();
+                    vm.pop()?;
+                }
+                FRAME_SET => {
+                    let id = vm.u64()?;
+                    let nth = vm.u32()? as usize;
+                    let value = vm.pop()?;
+
+                    mu.clear_values();
+                    if Frame::frame_let(mu, id, nth, value).is_none() {
+                        return Err(Exception::new(
+                            Condition::Type,
+                            "mu::let",
+                            Fixnum::as_tag(id as i64),
+                        ));
+                    }
+                }
+                RETURN => return Ok(TailCall::Value(vm.pop()?)),
+                _ => return Err(Exception::new(Condition::Type, "vm::execute", code)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        core::{
+            classes::Tag,
+            frame::Frame,
+            mu::{Core, Mu},
+            vm::{Bytecode, CONST, NATIVE, RETURN},
+        },
+        types::{
+            fixnum::Fixnum,
+            function::Function,
+            vecimage::{TypedVec, VecType},
+            vector::Core as _,
+        },
+    };
+
+    fn eval(mu: &Mu, src: &str) -> Tag {
+        mu.eval(
+            mu.compile(mu.read_string(src.to_string()).unwrap())
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn apply(mu: &Mu, func: Tag, argv: Vec<Tag>) -> Tag {
+        Frame {
+            func,
+            argv,
+            value: Tag::nil(),
+        }
+        .apply(mu, func)
+        .unwrap()
+    }
+
+    fn execute(mu: &Mu, code: Vec<u8>) -> bool {
+        let code = TypedVec::<Vec<u8>> { vec: code }.vec.to_vector().evict(mu);
+
+        Mu::execute(mu, code).is_ok()
+    }
+
+    #[test]
+    fn vm() {
+        let mu: &Mu = &Core::new("".to_string());
+        let func = eval(mu, "(:lambda (x) (:if x (mu:fx-add x 1) 0))");
+
+        assert!(!Function::code_of(mu, func).null_());
+
+        // the same lambda without code runs on the tree walker
+        let walker = Function::new(
+            Function::lambda_of(mu, func),
+            Function::nreq_of(mu, func),
+            Function::form_of(mu, func),
+            Function::frame_of(mu, func),
+            Tag::nil(),
+            Function::env_of(mu, func),
+            Function::arity_of(mu, func),
+        )
+        .evict(mu);
+
+        for arg in [Fixnum::as_tag(1), Tag::nil()] {
+            assert!(apply(mu, func, vec![arg]).eq_(apply(mu, walker, vec![arg])))
+        }
+        assert!(apply(mu, func, vec![Fixnum::as_tag(1)]).eq_(Fixnum::as_tag(2)));
+        assert!(apply(mu, func, vec![Tag::nil()]).eq_(Fixnum::as_tag(0)));
+    }
+
+    #[test]
+    fn malformed() {
+        let mu: &Mu = &Core::new("".to_string());
+
+        assert!(execute(mu, vec![CONST, 0, 0, 0, 0, 0, 0, 0, 0, RETURN]));
+        assert!(!execute(mu, vec![0xff]));
+        assert!(!execute(mu, vec![CONST, 0, 0]));
+        assert!(!execute(mu, vec![RETURN]));
+        assert!(!execute(mu, vec![NATIVE, 0xff, 0xff, 0, 0, 0]));
+        assert!(!execute(mu, vec![]));
+    }
+}
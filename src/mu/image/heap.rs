@@ -43,6 +43,7 @@ impl Heap {
             .read(true)
             .write(true)
             .create(true)
+            .truncate(true)
             .open(path)
             .expect("unable to open heap mmap");
 
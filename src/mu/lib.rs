@@ -2,6 +2,10 @@
 //  SPDX-License-Identifier: MIT
 
 //! libmu library
+#![cfg_attr(
+    test,
+    allow(clippy::assertions_on_constants, clippy::match_single_binding)
+)]
 #[macro_use]
 extern crate lazy_static;
 
@@ -3,5 +3,6 @@
 //  SPDX-License-Identifier: MIT
 
 //! system module
+pub mod signal;
 pub mod stream;
 pub mod sys;
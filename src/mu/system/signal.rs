@@ -0,0 +1,157 @@
+//  SPDX-FileCopyrightText: Copyright 2022 James M. Putnam (putnamjm.design@gmail.com)
+//  SPDX-License-Identifier: MIT
+
+//! system signals
+//!    Signal
+//!    catch
+//!    restore
+//!    poll
+use {
+    crate::{
+        core::{
+            classes::Tag,
+            exception,
+            exception::{Condition, Exception},
+            mu::{Core as _, Mu},
+        },
+        types::{
+            cons::{Cons, Core as _},
+            fixnum::Fixnum,
+        },
+    },
+    std::{
+        cell::RefCell,
+        collections::HashMap,
+        sync::atomic::{AtomicBool, Ordering},
+    },
+};
+
+pub const NSIG: usize = 32;
+
+// set asynchronously by the handler, consumed by poll
+#[allow(clippy::declare_interior_mutable_const)]
+const CLEAR: AtomicBool = AtomicBool::new(false);
+static PENDING: [AtomicBool; NSIG] = [CLEAR; NSIG];
+static ANY_PENDING: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_signal(signo: libc::c_int) {
+    if let Some(flag) = PENDING.get(signo as usize) {
+        flag.store(true, Ordering::SeqCst);
+        ANY_PENDING.store(true, Ordering::SeqCst);
+    }
+}
+
+// lisp handlers, by signal number
+pub struct Signal {
+    handlers: RefCell<HashMap<i32, Tag>>,
+}
+
+impl Default for Signal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Signal {
+    pub fn new() -> Self {
+        Signal {
+            handlers: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn sigaction(signo: i32, handler: libc::sighandler_t) -> bool {
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+
+            action.sa_sigaction = handler;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+
+            libc::sigaction(signo, &action, std::ptr::null_mut()) == 0
+        }
+    }
+
+    pub fn is_signal(signo: i64) -> bool {
+        signo > 0 && (signo as usize) < NSIG
+    }
+}
+
+pub trait Core {
+    fn catch(_: i32) -> bool;
+    fn clear(_: i32);
+    fn handler(_: &Mu, _: i32) -> Option<Tag>;
+    fn poll(_: &Mu) -> exception::Result<()>;
+    fn restore(_: i32) -> bool;
+    fn set_handler(_: &Mu, _: i32, _: Option<Tag>) -> Option<Tag>;
+}
+
+impl Core for Signal {
+    fn catch(signo: i32) -> bool {
+        Self::sigaction(
+            signo,
+            on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        )
+    }
+
+    fn restore(signo: i32) -> bool {
+        Self::sigaction(signo, libc::SIG_DFL)
+    }
+
+    fn clear(signo: i32) {
+        if let Some(flag) = PENDING.get(signo as usize) {
+            flag.store(false, Ordering::SeqCst)
+        }
+    }
+
+    fn handler(mu: &Mu, signo: i32) -> Option<Tag> {
+        mu.system.signals.handlers.borrow().get(&signo).copied()
+    }
+
+    fn set_handler(mu: &Mu, signo: i32, handler: Option<Tag>) -> Option<Tag> {
+        let mut handlers = mu.system.signals.handlers.borrow_mut();
+
+        match handler {
+            Some(func) => handlers.insert(signo, func),
+            None => handlers.remove(&signo),
+        }
+    }
+
+    // deliver at most one pending signal per poll. an unhandled
+    // SIGINT becomes an :intr exception.
+    fn poll(mu: &Mu) -> exception::Result<()> {
+        if !ANY_PENDING.load(Ordering::Relaxed) || !ANY_PENDING.swap(false, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let signo = match (1..NSIG).find(|signo| PENDING[*signo].swap(false, Ordering::SeqCst)) {
+            Some(signo) => signo as i32,
+            None => return Ok(()),
+        };
+
+        ANY_PENDING.store(true, Ordering::SeqCst);
+
+        match Self::handler(mu, signo) {
+            Some(func) => {
+                mu.apply(func, Cons::list(mu, &[Fixnum::as_tag(signo as i64)]))?;
+                Ok(())
+            }
+            None if signo == libc::SIGINT => Err(Exception::new(
+                Condition::Interrupt,
+                "mu:eval",
+                Fixnum::as_tag(signo as i64),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::system::signal::Signal;
+
+    #[test]
+    fn signal() {
+        assert!(Signal::is_signal(2));
+        assert!(!Signal::is_signal(0));
+    }
+}
@@ -12,6 +12,9 @@ use {
         cell::{Ref, RefCell, RefMut},
         fs,
         io::{Read, Write},
+        net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+        os::unix::net::{UnixListener, UnixStream},
+        path::PathBuf,
     },
 };
 
@@ -20,8 +23,18 @@ pub const STDIN: usize = 0x80000000;
 pub const STDOUT: usize = 0x80000001;
 pub const STDERR: usize = 0x80000002;
 
+// system stream descriptors
+pub enum Descriptor {
+    Closed,
+    File(fs::File),
+    TcpListener(TcpListener),
+    TcpStream(TcpStream),
+    UnixListener(UnixListener, PathBuf),
+    UnixStream(UnixStream),
+}
+
 pub struct Stream {
-    filetab: RefCell<Vec<RefCell<fs::File>>>,
+    filetab: RefCell<Vec<RefCell<Descriptor>>>,
 }
 
 impl Default for Stream {
@@ -36,19 +49,52 @@ impl Stream {
             filetab: RefCell::new(Vec::new()),
         }
     }
+
+    fn push(stream: &Stream, desc: Descriptor) -> usize {
+        let mut tab_ref: RefMut<Vec<RefCell<Descriptor>>> = stream.filetab.borrow_mut();
+        let index = tab_ref.len();
+
+        tab_ref.push(RefCell::new(desc));
+        index
+    }
+
+    // host:port addresses are tcp, anything else is a unix-domain path
+    fn tcp_addr(addr: &str) -> Option<exception::Result<SocketAddr>> {
+        let (host, port) = addr.rsplit_once(':')?;
+
+        if host.is_empty() || host.contains('/') || port.parse::<u16>().is_err() {
+            return None;
+        }
+
+        let err = Exception::new(Condition::Open, "system::socket", Tag::nil());
+
+        // we only talk to ourselves
+        Some(match addr.to_socket_addrs() {
+            Ok(mut addrs) => match addrs.find(|sa| sa.ip().is_loopback()) {
+                Some(sa) => Ok(sa),
+                None => Err(err),
+            },
+            Err(_) => Err(err),
+        })
+    }
 }
 
 pub trait Core {
+    fn accept(_: &Stream, _: usize) -> exception::Result<usize>;
     fn close(_: &Stream, _: usize);
+    fn connect(_: &Stream, _: &str) -> exception::Result<usize>;
     fn flush(_: &Stream, _: usize);
+    fn listen(_: &Stream, _: &str) -> exception::Result<usize>;
     fn open(_: &Stream, _: &str, is_input: bool) -> exception::Result<usize>;
     fn read_byte(_: &Stream, _: usize) -> exception::Result<Option<u8>>;
+    fn read_bytes(_: &Stream, _: usize, _: usize) -> exception::Result<Vec<u8>>;
     fn write_byte(_: &Stream, _: usize, _: u8) -> exception::Result<Option<()>>;
+    fn write_bytes(_: &Stream, _: usize, _: &[u8]) -> exception::Result<()>;
 }
 
 impl Core for Stream {
     fn flush(stream: &Stream, index: usize) {
-        let tab_ref: Ref<Vec<RefCell<fs::File>>> = stream.filetab.borrow();
+        let tab_ref: Ref<Vec<RefCell<Descriptor>>> = stream.filetab.borrow();
 
         match index {
             STDOUT => {
@@ -61,12 +107,20 @@ impl Core for Stream {
                 if index >= tab_ref.len() {
                     panic!();
                 }
+
+                let mut desc_ref: RefMut<Descriptor> = tab_ref[index].borrow_mut();
+                let _ = match &mut *desc_ref {
+                    Descriptor::File(file) => file.flush(),
+                    Descriptor::TcpStream(socket) => socket.flush(),
+                    Descriptor::UnixStream(socket) => socket.flush(),
+                    _ => Ok(()),
+                };
             }
         }
     }
 
     fn close(stream: &Stream, index: usize) {
-        let tab_ref: Ref<Vec<RefCell<fs::File>>> = stream.filetab.borrow();
+        let tab_ref: Ref<Vec<RefCell<Descriptor>>> = stream.filetab.borrow();
 
         match index {
             STDIN | STDOUT | STDERR => (),
@@ -74,8 +128,13 @@ impl Core for Stream {
                 if index >= tab_ref.len() {
                     panic!();
                 } else {
-                    let file: Ref<fs::File> = tab_ref[index].borrow();
-                    std::mem::drop(file);
+                    let desc = tab_ref[index].replace(Descriptor::Closed);
+
+                    if let Descriptor::UnixListener(_, path) = &desc {
+                        let _ = fs::remove_file(path);
+                    }
+
+                    std::mem::drop(desc);
                 }
             }
         }
@@ -85,108 +144,233 @@ impl Core for Stream {
         let file = if is_input {
             match fs::File::open(path) {
                 Ok(file) => file,
-                Err(_) => {
-                    return Err(Exception {
-                        condition: Condition::Open,
-                        source: "system::open".to_string(),
-                        tag: Tag::nil(),
-                    })
-                }
+                Err(_) => return Err(Exception::new(Condition::Open, "system::open", Tag::nil())),
             }
         } else {
             match fs::File::create(path) {
                 Ok(file) => file,
-                Err(_) => {
-                    return Err(Exception {
-                        condition: Condition::Open,
-                        source: "system::open".to_string(),
-                        tag: Tag::nil(),
-                    })
-                }
+                Err(_) => return Err(Exception::new(Condition::Open, "system::open", Tag::nil())),
             }
         };
 
-        let desc = RefCell::new(file);
-        let mut tab_ref: RefMut<Vec<RefCell<fs::File>>> = stream.filetab.borrow_mut();
-        let index = tab_ref.len();
+        Ok(Self::push(stream, Descriptor::File(file)))
+    }
+
+    fn listen(stream: &Stream, addr: &str) -> exception::Result<usize> {
+        let desc = match Self::tcp_addr(addr) {
+            Some(sa) => TcpListener::bind(sa?).map(Descriptor::TcpListener),
+            None => UnixListener::bind(addr)
+                .map(|listener| Descriptor::UnixListener(listener, PathBuf::from(addr))),
+        };
+
+        match desc {
+            Ok(desc) => Ok(Self::push(stream, desc)),
+            Err(_) => Err(Exception::new(
+                Condition::Open,
+                "system::listen",
+                Tag::nil(),
+            )),
+        }
+    }
+
+    fn connect(stream: &Stream, addr: &str) -> exception::Result<usize> {
+        let desc = match Self::tcp_addr(addr) {
+            Some(sa) => TcpStream::connect(sa?).map(Descriptor::TcpStream),
+            None => UnixStream::connect(addr).map(Descriptor::UnixStream),
+        };
+
+        match desc {
+            Ok(desc) => Ok(Self::push(stream, desc)),
+            Err(_) => Err(Exception::new(
+                Condition::Open,
+                "system::connect",
+                Tag::nil(),
+            )),
+        }
+    }
+
+    fn accept(stream: &Stream, index: usize) -> exception::Result<usize> {
+        let desc = {
+            let tab_ref: Ref<Vec<RefCell<Descriptor>>> = stream.filetab.borrow();
+
+            if index >= tab_ref.len() {
+                None
+            } else {
+                let desc_ref: Ref<Descriptor> = tab_ref[index].borrow();
+                match &*desc_ref {
+                    Descriptor::TcpListener(listener) => Some(
+                        listener
+                            .accept()
+                            .map(|(socket, _)| Descriptor::TcpStream(socket)),
+                    ),
+                    Descriptor::UnixListener(listener, _) => Some(
+                        listener
+                            .accept()
+                            .map(|(socket, _)| Descriptor::UnixStream(socket)),
+                    ),
+                    _ => None,
+                }
+            }
+        };
 
-        tab_ref.push(desc);
-        Ok(index)
+        match desc {
+            Some(Ok(desc)) => Ok(Self::push(stream, desc)),
+            Some(Err(_)) => Err(Exception::new(
+                Condition::Open,
+                "system::accept",
+                Tag::nil(),
+            )),
+            None => Err(Exception::new(
+                Condition::Stream,
+                "system::accept",
+                Tag::nil(),
+            )),
+        }
     }
 
     fn read_byte(stream: &Stream, stream_id: usize) -> exception::Result<Option<u8>> {
-        let tab_ref: Ref<Vec<RefCell<fs::File>>> = stream.filetab.borrow();
+        let tab_ref: Ref<Vec<RefCell<Descriptor>>> = stream.filetab.borrow();
         let mut buf = [0; 1];
 
-        match stream_id {
-            STDIN => match std::io::stdin().read(&mut buf) {
-                Ok(nread) => {
-                    if nread == 0 {
-                        Ok(None)
-                    } else {
-                        Ok(Some(buf[0]))
+        let nread = match stream_id {
+            STDIN => std::io::stdin().read(&mut buf),
+            _ if stream_id < tab_ref.len() => {
+                let mut desc_ref: RefMut<Descriptor> = tab_ref[stream_id].borrow_mut();
+                match &mut *desc_ref {
+                    Descriptor::File(file) => file.read(&mut buf),
+                    Descriptor::TcpStream(socket) => socket.read(&mut buf),
+                    Descriptor::UnixStream(socket) => socket.read(&mut buf),
+                    _ => {
+                        return Err(Exception::new(
+                            Condition::Stream,
+                            "system::read_byte",
+                            Tag::nil(),
+                        ))
                     }
                 }
-                Err(_) => Err(Exception {
-                    condition: Condition::Read,
-                    source: "system::read_byte".to_string(),
-                    tag: Tag::nil(),
-                }),
-            },
+            }
+            _ => panic!(),
+        };
+
+        match nread {
+            Ok(nread) => {
+                if nread == 0 {
+                    Ok(None)
+                } else {
+                    Ok(Some(buf[0]))
+                }
+            }
+            Err(_) => Err(Exception::new(
+                Condition::Read,
+                "system::read_byte",
+                Tag::nil(),
+            )),
+        }
+    }
+
+    // read up to nbytes, short only at end of file
+    // the buffer grows with what's read, not with what's asked for
+    fn read_bytes(stream: &Stream, stream_id: usize, nbytes: usize) -> exception::Result<Vec<u8>> {
+        let tab_ref: Ref<Vec<RefCell<Descriptor>>> = stream.filetab.borrow();
+        let mut buf = Vec::new();
+        let limit = nbytes as u64;
+
+        let nread = match stream_id {
+            STDIN => std::io::stdin().take(limit).read_to_end(&mut buf),
+            _ if stream_id < tab_ref.len() => {
+                let mut desc_ref: RefMut<Descriptor> = tab_ref[stream_id].borrow_mut();
+                match &mut *desc_ref {
+                    Descriptor::File(file) => file.take(limit).read_to_end(&mut buf),
+                    Descriptor::TcpStream(socket) => socket.take(limit).read_to_end(&mut buf),
+                    Descriptor::UnixStream(socket) => socket.take(limit).read_to_end(&mut buf),
+                    _ => {
+                        return Err(Exception::new(
+                            Condition::Stream,
+                            "system::read_bytes",
+                            Tag::nil(),
+                        ))
+                    }
+                }
+            }
+            _ => panic!(),
+        };
+
+        match nread {
+            Ok(_) => Ok(buf),
+            Err(_) => Err(Exception::new(
+                Condition::Read,
+                "system::read_bytes",
+                Tag::nil(),
+            )),
+        }
+    }
+
+    fn write_bytes(stream: &Stream, stream_id: usize, bytes: &[u8]) -> exception::Result<()> {
+        let tab_ref: Ref<Vec<RefCell<Descriptor>>> = stream.filetab.borrow();
+
+        let nwrite = match stream_id {
+            STDOUT => std::io::stdout().write_all(bytes),
+            STDERR => std::io::stderr().write_all(bytes),
             _ if stream_id < tab_ref.len() => {
-                let mut file_ref: RefMut<fs::File> = tab_ref[stream_id].borrow_mut();
-                match file_ref.read(&mut buf) {
-                    Ok(nread) => {
-                        if nread == 0 {
-                            Ok(None)
-                        } else {
-                            Ok(Some(buf[0]))
-                        }
+                let mut desc_ref: RefMut<Descriptor> = tab_ref[stream_id].borrow_mut();
+                match &mut *desc_ref {
+                    Descriptor::File(file) => file.write_all(bytes),
+                    Descriptor::TcpStream(socket) => socket.write_all(bytes),
+                    Descriptor::UnixStream(socket) => socket.write_all(bytes),
+                    _ => {
+                        return Err(Exception::new(
+                            Condition::Stream,
+                            "system::write_bytes",
+                            Tag::nil(),
+                        ))
                     }
-                    Err(_) => Err(Exception {
-                        condition: Condition::Read,
-                        source: "system::read_byte".to_string(),
-                        tag: Tag::nil(),
-                    }),
                 }
             }
             _ => panic!(),
+        };
+
+        match nwrite {
+            Ok(_) => Ok(()),
+            Err(_) => Err(Exception::new(
+                Condition::Write,
+                "system::write_bytes",
+                Tag::nil(),
+            )),
         }
     }
 
     fn write_byte(stream: &Stream, stream_id: usize, byte: u8) -> exception::Result<Option<()>> {
-        let tab_ref: Ref<Vec<RefCell<fs::File>>> = stream.filetab.borrow();
+        let tab_ref: Ref<Vec<RefCell<Descriptor>>> = stream.filetab.borrow();
         let buf = [byte; 1];
 
-        match stream_id {
-            STDOUT => match std::io::stdout().write(&buf) {
-                Ok(_) => Ok(None),
-                Err(_) => Err(Exception {
-                    condition: Condition::Write,
-                    source: "system::write_byte".to_string(),
-                    tag: Tag::nil(),
-                }),
-            },
-            STDERR => match std::io::stderr().write(&buf) {
-                Ok(_) => Ok(None),
-                Err(_) => Err(Exception {
-                    condition: Condition::Write,
-                    source: "system::write_byte".to_string(),
-                    tag: Tag::nil(),
-                }),
-            },
+        let nwrite = match stream_id {
+            STDOUT => std::io::stdout().write_all(&buf),
+            STDERR => std::io::stderr().write_all(&buf),
             _ if stream_id < tab_ref.len() => {
-                let mut file_ref: RefMut<fs::File> = tab_ref[stream_id].borrow_mut();
-                match file_ref.write(&buf) {
-                    Ok(_) => Ok(None),
-                    Err(_) => Err(Exception {
-                        condition: Condition::Write,
-                        source: "system::write_byte".to_string(),
-                        tag: Tag::nil(),
-                    }),
+                let mut desc_ref: RefMut<Descriptor> = tab_ref[stream_id].borrow_mut();
+                match &mut *desc_ref {
+                    Descriptor::File(file) => file.write_all(&buf),
+                    Descriptor::TcpStream(socket) => socket.write_all(&buf),
+                    Descriptor::UnixStream(socket) => socket.write_all(&buf),
+                    _ => {
+                        return Err(Exception::new(
+                            Condition::Stream,
+                            "system::write_byte",
+                            Tag::nil(),
+                        ))
+                    }
                 }
             }
             _ => panic!(),
+        };
+
+        match nwrite {
+            Ok(_) => Ok(None),
+            Err(_) => Err(Exception::new(
+                Condition::Write,
+                "system::write_byte",
+                Tag::nil(),
+            )),
         }
     }
 }
@@ -2,10 +2,41 @@
 //  SPDX-License-Identifier: MIT
 
 //! system interface
-use crate::system::{stream, stream::Stream};
+use {
+    crate::{
+        core::{
+            classes::{Tag, Type},
+            exception,
+            exception::{Condition, Exception},
+            frame::Frame,
+            mu::Mu,
+        },
+        system::{
+            signal::{Core as _, Signal},
+            stream,
+            stream::Stream,
+        },
+        types::{
+            cons::{Cons, Core as _},
+            fixnum::Fixnum,
+            r#struct::Struct,
+            symbol::{Core as _, Symbol},
+            vecimage::{TypedVec, VecType},
+            vector::{Core as _, Vector},
+        },
+    },
+    std::{
+        env, fs,
+        os::unix::fs::PermissionsExt,
+        process, thread,
+        time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    },
+};
 
 pub struct System {
     pub streams: Stream,
+    pub signals: Signal,
+    pub start: Instant,
 }
 
 impl Default for System {
@@ -18,18 +49,472 @@ impl System {
     pub fn new() -> Self {
         System {
             streams: stream::Stream::new(),
+            signals: Signal::new(),
+            start: Instant::now(),
         }
     }
+
+    fn string_arg(mu: &Mu, tag: Tag, src: &str) -> exception::Result<String> {
+        match Tag::type_of(mu, tag) {
+            Type::Vector if Vector::type_of(mu, tag) == Type::Char => {
+                Ok(Vector::as_string(mu, tag))
+            }
+            _ => Err(Exception::new(Condition::Type, src, tag)),
+        }
+    }
+
+    fn usec(duration: Duration) -> Tag {
+        Fixnum::as_tag(duration.as_micros() as i64)
+    }
+
+    fn path_result<T>(result: std::io::Result<T>, src: &str, path: Tag) -> exception::Result<T> {
+        result.map_err(|_| Exception::new(Condition::System, src, path))
+    }
+}
+
+pub trait MuFunction {
+    fn sys_chdir(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_clock(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_cwd(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_exists(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_exit(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_getenv(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_logname(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_mkdir(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_pid(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_procenv(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_readdir(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_realpath(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_rename(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_rmdir(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_setenv(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_signal(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_sleep(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_stat(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_system(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_time(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_uid(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_unlink(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn sys_utimes(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+}
+
+impl MuFunction for System {
+    fn sys_cwd(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        fp.value = match env::current_dir() {
+            Ok(path) => Vector::from_string(&path.to_string_lossy()).evict(mu),
+            Err(_) => return Err(Exception::new(Condition::System, "system:cwd", Tag::nil())),
+        };
+
+        Ok(())
+    }
+
+    fn sys_chdir(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let path = fp.argv[0];
+
+        match env::set_current_dir(Self::string_arg(mu, path, "system:chdir")?) {
+            Ok(_) => {
+                fp.value = path;
+                Ok(())
+            }
+            Err(_) => Err(Exception::new(Condition::System, "system:chdir", path)),
+        }
+    }
+
+    fn sys_getenv(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let name = fp.argv[0];
+
+        fp.value = match env::var(Self::string_arg(mu, name, "system:getenv")?) {
+            Ok(value) => Vector::from_string(&value).evict(mu),
+            Err(_) => Tag::nil(),
+        };
+
+        Ok(())
+    }
+
+    fn sys_setenv(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let name = Self::string_arg(mu, fp.argv[0], "system:setenv")?;
+        let value = fp.argv[1];
+
+        if name.is_empty() || name.contains('=') || name.contains('\0') {
+            return Err(Exception::new(
+                Condition::Range,
+                "system:setenv",
+                fp.argv[0],
+            ));
+        }
+
+        let string = Self::string_arg(mu, value, "system:setenv")?;
+        if string.contains('\0') {
+            return Err(Exception::new(Condition::Range, "system:setenv", value));
+        }
+
+        env::set_var(name, string);
+
+        fp.value = value;
+        Ok(())
+    }
+
+    fn sys_procenv(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let vars = env::vars()
+            .map(|(name, value)| {
+                Cons::new(
+                    Vector::from_string(&name).evict(mu),
+                    Vector::from_string(&value).evict(mu),
+                )
+                .evict(mu)
+            })
+            .collect::<Vec<Tag>>();
+
+        fp.value = Cons::list(mu, &vars);
+        Ok(())
+    }
+
+    fn sys_uid(_: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        fp.value = Fixnum::as_tag(unsafe { libc::getuid() } as i64);
+
+        Ok(())
+    }
+
+    fn sys_logname(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let passwd = unsafe { libc::getpwuid(libc::getuid()) };
+
+        let name = if passwd.is_null() {
+            env::var("LOGNAME").or_else(|_| env::var("USER")).ok()
+        } else {
+            Some(
+                unsafe { std::ffi::CStr::from_ptr((*passwd).pw_name) }
+                    .to_string_lossy()
+                    .to_string(),
+            )
+        };
+
+        fp.value = match name {
+            Some(name) => Vector::from_string(&name).evict(mu),
+            None => Tag::nil(),
+        };
+
+        Ok(())
+    }
+
+    fn sys_pid(_: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        fp.value = Fixnum::as_tag(process::id() as i64);
+
+        Ok(())
+    }
+
+    // wall clock microseconds since the epoch
+    fn sys_time(_: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        fp.value = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(duration) => Self::usec(duration),
+            Err(_) => return Err(Exception::new(Condition::System, "system:time", Tag::nil())),
+        };
+
+        Ok(())
+    }
+
+    // monotonic microseconds since the runtime started
+    fn sys_clock(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        fp.value = Self::usec(mu.system.start.elapsed());
+
+        Ok(())
+    }
+
+    fn sys_sleep(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let usec = fp.argv[0];
+
+        match Tag::type_of(mu, usec) {
+            Type::Fixnum if Fixnum::as_i64(mu, usec) >= 0 => {
+                thread::sleep(Duration::from_micros(Fixnum::as_i64(mu, usec) as u64));
+                fp.value = usec;
+                Ok(())
+            }
+            _ => Err(Exception::new(Condition::Type, "system:sleep", usec)),
+        }
+    }
+
+    // #(:fixnum elapsed process user) in microseconds
+    fn sys_utimes(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+
+        if unsafe { libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) } != 0 {
+            return Err(Exception::new(
+                Condition::System,
+                "system:utimes",
+                Tag::nil(),
+            ));
+        }
+
+        let usage = unsafe { usage.assume_init() };
+        let usec = |tv: libc::timeval| tv.tv_sec * 1_000_000 + tv.tv_usec;
+
+        let vec = vec![
+            mu.system.start.elapsed().as_micros() as i64,
+            usec(usage.ru_utime) + usec(usage.ru_stime),
+            usec(usage.ru_utime),
+        ];
+
+        fp.value = TypedVec::<Vec<i64>> { vec }.vec.to_vector().evict(mu);
+        Ok(())
+    }
+
+    fn sys_system(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let cmd = fp.argv[0];
+
+        fp.value = match process::Command::new("sh")
+            .arg("-c")
+            .arg(Self::string_arg(mu, cmd, "system:system")?)
+            .status()
+        {
+            Ok(status) => Fixnum::as_tag(status.code().unwrap_or(-1) as i64),
+            Err(_) => return Err(Exception::new(Condition::System, "system:system", cmd)),
+        };
+
+        Ok(())
+    }
+
+    // install a lisp handler for a signal, () restores the default.
+    // returns the previous handler.
+    fn sys_signal(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let signo = fp.argv[0];
+        let handler = fp.argv[1];
+
+        let signo = match Tag::type_of(mu, signo) {
+            Type::Fixnum if Signal::is_signal(Fixnum::as_i64(mu, signo)) => {
+                Fixnum::as_i64(mu, signo) as i32
+            }
+            _ => return Err(Exception::new(Condition::Type, "system:signal", signo)),
+        };
+
+        let installed = match Tag::type_of(mu, handler) {
+            Type::Null => Signal::restore(signo),
+            Type::Function => Signal::catch(signo),
+            _ => return Err(Exception::new(Condition::Type, "system:signal", handler)),
+        };
+
+        if !installed {
+            return Err(Exception::new(
+                Condition::System,
+                "system:signal",
+                fp.argv[0],
+            ));
+        }
+
+        let handler = match Tag::type_of(mu, handler) {
+            Type::Null => None,
+            _ => Some(handler),
+        };
+
+        fp.value = Signal::set_handler(mu, signo, handler).unwrap_or_else(Tag::nil);
+        Ok(())
+    }
+
+    fn sys_exit(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let rc = fp.argv[0];
+
+        match Tag::type_of(mu, rc) {
+            Type::Fixnum => process::exit(Fixnum::as_i64(mu, rc) as i32),
+            _ => Err(Exception::new(Condition::Type, "system:exit", rc)),
+        }
+    }
+
+    fn sys_exists(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let path = Self::string_arg(mu, fp.argv[0], "system:exists")?;
+
+        fp.value = if fs::symlink_metadata(path).is_ok() {
+            Tag::t()
+        } else {
+            Tag::nil()
+        };
+
+        Ok(())
+    }
+
+    // #S(:stat size kind mtime mode), mtime in microseconds since the epoch
+    fn sys_stat(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let path = fp.argv[0];
+        let meta = Self::path_result(
+            fs::symlink_metadata(Self::string_arg(mu, path, "system:stat")?),
+            "system:stat",
+            path,
+        )?;
+
+        let kind = if meta.file_type().is_symlink() {
+            "symlink"
+        } else if meta.is_dir() {
+            "dir"
+        } else if meta.is_file() {
+            "file"
+        } else {
+            "other"
+        };
+
+        let mtime = match meta.modified().map(|time| time.duration_since(UNIX_EPOCH)) {
+            Ok(Ok(duration)) => Self::usec(duration),
+            _ => Tag::nil(),
+        };
+
+        fp.value = Struct::to_tag(
+            mu,
+            Symbol::keyword("stat"),
+            vec![
+                Fixnum::as_tag(meta.len() as i64),
+                Symbol::keyword(kind),
+                mtime,
+                Fixnum::as_tag((meta.permissions().mode() & 0o7777) as i64),
+            ],
+        );
+
+        Ok(())
+    }
+
+    // entry names, sorted, without . and ..
+    fn sys_readdir(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let path = fp.argv[0];
+        let dir = Self::path_result(
+            fs::read_dir(Self::string_arg(mu, path, "system:readdir")?),
+            "system:readdir",
+            path,
+        )?;
+
+        let mut names = Self::path_result(
+            dir.map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().to_string()))
+                .collect::<std::io::Result<Vec<String>>>(),
+            "system:readdir",
+            path,
+        )?;
+
+        names.sort();
+
+        let names = names
+            .iter()
+            .map(|name| Vector::from_string(name).evict(mu))
+            .collect::<Vec<Tag>>();
+
+        fp.value = Cons::list(mu, &names);
+        Ok(())
+    }
+
+    fn sys_realpath(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let path = fp.argv[0];
+        let canon = Self::path_result(
+            fs::canonicalize(Self::string_arg(mu, path, "system:realpath")?),
+            "system:realpath",
+            path,
+        )?;
+
+        fp.value = Vector::from_string(&canon.to_string_lossy()).evict(mu);
+        Ok(())
+    }
+
+    fn sys_rename(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let from = fp.argv[0];
+        let to = fp.argv[1];
+
+        Self::path_result(
+            fs::rename(
+                Self::string_arg(mu, from, "system:rename")?,
+                Self::string_arg(mu, to, "system:rename")?,
+            ),
+            "system:rename",
+            from,
+        )?;
+
+        fp.value = to;
+        Ok(())
+    }
+
+    fn sys_unlink(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let path = fp.argv[0];
+
+        Self::path_result(
+            fs::remove_file(Self::string_arg(mu, path, "system:unlink")?),
+            "system:unlink",
+            path,
+        )?;
+
+        fp.value = path;
+        Ok(())
+    }
+
+    fn sys_mkdir(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let path = fp.argv[0];
+
+        Self::path_result(
+            fs::create_dir(Self::string_arg(mu, path, "system:mkdir")?),
+            "system:mkdir",
+            path,
+        )?;
+
+        fp.value = path;
+        Ok(())
+    }
+
+    fn sys_rmdir(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let path = fp.argv[0];
+
+        Self::path_result(
+            fs::remove_dir(Self::string_arg(mu, path, "system:rmdir")?),
+            "system:rmdir",
+            path,
+        )?;
+
+        fp.value = path;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::system::sys::System;
+    use crate::{
+        core::{
+            classes::Tag,
+            exception::Condition,
+            frame::Frame,
+            mu::{Core, Mu},
+        },
+        system::sys::{MuFunction, System},
+        types::vector::{Core as _, Vector},
+    };
+
+    fn call(
+        mu: &Mu,
+        native: fn(&Mu, &mut Frame) -> crate::core::exception::Result<()>,
+        argv: Vec<Tag>,
+    ) -> crate::core::exception::Result<Tag> {
+        let mut fp = Frame {
+            func: Tag::nil(),
+            argv,
+            value: Tag::nil(),
+        };
+
+        native(mu, &mut fp).map(|_| fp.value)
+    }
 
     #[test]
-    fn system() {
-        match System::new() {
-            _ => assert_eq!(true, true),
+    fn env() {
+        let mu: &Mu = &Core::new("".to_string());
+        let string = |str: &str| Vector::from_string(str).evict(mu);
+
+        call(
+            mu,
+            System::sys_setenv,
+            vec![string("MU_SYS_TEST"), string("value")],
+        )
+        .unwrap();
+        let value = call(mu, System::sys_getenv, vec![string("MU_SYS_TEST")]).unwrap();
+        assert_eq!(Vector::as_string(mu, value), "value");
+
+        let value = call(mu, System::sys_getenv, vec![string("MU_SYS_UNSET")]).unwrap();
+        assert!(value.null_());
+
+        for (name, value) in [("MU=SYS", "value"), ("", "value"), ("MU_SYS_TEST", "a\0b")] {
+            match call(mu, System::sys_setenv, vec![string(name), string(value)]) {
+                Err(e) => assert!(e.condition == Condition::Range),
+                Ok(_) => panic!("setenv {name:?} {value:?}"),
+            }
         }
+
+        let value = call(mu, System::sys_getenv, vec![string("MU_SYS_TEST")]).unwrap();
+        assert_eq!(Vector::as_string(mu, value), "value");
     }
 }
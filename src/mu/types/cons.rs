@@ -150,8 +150,8 @@ impl Core for Cons {
     fn write(mu: &Mu, cons: Tag, escape: bool, stream: Tag) -> exception::Result<()> {
         let car = Self::car(mu, cons);
 
-        mu.write_string("(".to_string(), stream).unwrap();
-        mu.write(car, escape, stream).unwrap();
+        mu.write_string("(".to_string(), stream)?;
+        mu.write(car, escape, stream)?;
 
         let mut tail = Self::cdr(mu, cons);
 
@@ -159,14 +159,14 @@ impl Core for Cons {
         loop {
             match Tag::type_of(mu, tail) {
                 Type::Cons => {
-                    mu.write_string(" ".to_string(), stream).unwrap();
-                    mu.write(Self::car(mu, tail), escape, stream).unwrap();
+                    mu.write_string(" ".to_string(), stream)?;
+                    mu.write(Self::car(mu, tail), escape, stream)?;
                     tail = Self::cdr(mu, tail);
                 }
                 _ if tail.null_() => break,
                 _ => {
-                    mu.write_string(" . ".to_string(), stream).unwrap();
-                    mu.write(tail, escape, stream).unwrap();
+                    mu.write_string(" . ".to_string(), stream)?;
+                    mu.write(tail, escape, stream)?;
                     break;
                 }
             }
@@ -295,7 +295,18 @@ pub trait MuFunction {
 
 impl MuFunction for Cons {
     fn mu_append(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
-        fp.value = Self::append(mu, fp.argv[0], fp.argv[1]);
+        let list = fp.argv[0];
+        let mut iter = ProperListIter::new(mu, list);
+        let elements: Vec<Tag> = iter.by_ref().map(|cons| Self::car(mu, cons)).collect();
+
+        if !iter.cons.null_() {
+            return Err(Exception::new(Condition::Type, "mu:append", list));
+        }
+
+        fp.value = elements
+            .into_iter()
+            .rev()
+            .fold(fp.argv[1], |tail, el| Cons::new(el, tail).evict(mu));
 
         Ok(())
     }
@@ -404,17 +415,19 @@ impl<'a> ProperListIter<'a> {
     }
 }
 
-// proper lists only
+// proper lists only, iteration stops at a dotted tail and
+// leaves it in cons
 impl<'a> Iterator for ProperListIter<'a> {
     type Item = Tag;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.cons.null_() {
-            None
-        } else {
-            let cons = self.cons;
-            self.cons = Cons::cdr(self.mu, self.cons);
-            Some(cons)
+        match Tag::type_of(self.mu, self.cons) {
+            Type::Cons => {
+                let cons = self.cons;
+                self.cons = Cons::cdr(self.mu, self.cons);
+                Some(cons)
+            }
+            _ => None,
         }
     }
 }
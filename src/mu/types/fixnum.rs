@@ -23,6 +23,10 @@ pub enum Fixnum {
 }
 
 impl Fixnum {
+    // fixnums are 62 bits
+    const MAX: i64 = (1 << 61) - 1;
+    const MIN: i64 = -(1 << 61);
+
     // u64 to tag
     pub fn as_tag(fx: i64) -> Tag {
         // we're implicitly or'ing in the fixnum base tag type 0 here
@@ -36,6 +40,14 @@ impl Fixnum {
             _ => panic!(),
         }
     }
+
+    // an arithmetic result as a fixnum, if it didn't overflow
+    fn checked(fx: Option<i64>, src: &str, tag: Tag) -> exception::Result<Tag> {
+        match fx {
+            Some(fx) if (Self::MIN..=Self::MAX).contains(&fx) => Ok(Self::as_tag(fx)),
+            _ => Err(Exception::new(Condition::Range, src, tag)),
+        }
+    }
 }
 
 pub trait Core {
@@ -71,7 +83,11 @@ impl MuFunction for Fixnum {
         match Tag::type_of(mu, fx0) {
             Type::Fixnum => match Tag::type_of(mu, fx1) {
                 Type::Fixnum => {
-                    fp.value = Self::as_tag(Fixnum::as_i64(mu, fx0) + Fixnum::as_i64(mu, fx1));
+                    fp.value = Self::checked(
+                        Fixnum::as_i64(mu, fx0).checked_add(Fixnum::as_i64(mu, fx1)),
+                        "mu:fx-add",
+                        fx0,
+                    )?;
                     Ok(())
                 }
                 _ => Err(Exception::new(Condition::Type, "mu:fx-add", fx1)),
@@ -87,7 +103,11 @@ impl MuFunction for Fixnum {
         match Tag::type_of(mu, fx0) {
             Type::Fixnum => match Tag::type_of(mu, fx1) {
                 Type::Fixnum => {
-                    fp.value = Self::as_tag(Fixnum::as_i64(mu, fx0) - Fixnum::as_i64(mu, fx1));
+                    fp.value = Self::checked(
+                        Fixnum::as_i64(mu, fx0).checked_sub(Fixnum::as_i64(mu, fx1)),
+                        "mu:fx-sub",
+                        fx0,
+                    )?;
                     Ok(())
                 }
                 _ => Err(Exception::new(Condition::Type, "mu:fx-sub", fx1)),
@@ -103,7 +123,11 @@ impl MuFunction for Fixnum {
         match Tag::type_of(mu, fx0) {
             Type::Fixnum => match Tag::type_of(mu, fx1) {
                 Type::Fixnum => {
-                    fp.value = Self::as_tag(Fixnum::as_i64(mu, fx0) * Fixnum::as_i64(mu, fx1));
+                    fp.value = Self::checked(
+                        Fixnum::as_i64(mu, fx0).checked_mul(Fixnum::as_i64(mu, fx1)),
+                        "mu:fx-mul",
+                        fx0,
+                    )?;
                     Ok(())
                 }
                 _ => Err(Exception::new(Condition::Type, "mu:fx-mul", fx1)),
@@ -147,7 +171,7 @@ impl MuFunction for Fixnum {
                         return Err(Exception::new(Condition::ZeroDivide, "mu:fx-div", fx0));
                     }
 
-                    fp.value = Self::as_tag(dividend / divisor);
+                    fp.value = Self::checked(dividend.checked_div(divisor), "mu:fx-div", fx0)?;
                     Ok(())
                 }
                 _ => Err(Exception::new(Condition::Type, "mu:fx-div", fx1)),
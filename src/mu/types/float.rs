@@ -29,7 +29,7 @@ impl Float {
         Tag::to_direct(u32::from_le_bytes(bytes) as u64, 0, DirectType::Float)
     }
 
-    pub fn as_f32(mu: &Mu, tag: Tag) -> f32 {
+    pub fn as_f32(mu: &Mu, tag: Tag) -> exception::Result<f32> {
         match Tag::type_of(mu, tag) {
             Type::Float => {
                 let data = tag.data(mu).to_le_bytes();
@@ -38,9 +38,9 @@ impl Float {
                 for (dst, src) in fl.iter_mut().zip(data.iter()) {
                     *dst = *src
                 }
-                f32::from_le_bytes(fl)
+                Ok(f32::from_le_bytes(fl))
             }
-            _ => panic!(),
+            _ => Err(Exception::new(Condition::Type, "float::as_f32", tag)),
         }
     }
 }
@@ -56,7 +56,7 @@ impl Core for Float {
     }
 
     fn write(mu: &Mu, tag: Tag, _escape: bool, stream: Tag) -> exception::Result<()> {
-        mu.write_string(format!("{:.4}", Self::as_f32(mu, tag)), stream)
+        mu.write_string(format!("{:.4}", Self::as_f32(mu, tag)?), stream)
     }
 }
 
@@ -76,7 +76,7 @@ impl MuFunction for Float {
         match Tag::type_of(mu, fl0) {
             Type::Float => match Tag::type_of(mu, fl1) {
                 Type::Float => {
-                    fp.value = Self::as_tag(Self::as_f32(mu, fl0) + Self::as_f32(mu, fl1));
+                    fp.value = Self::as_tag(Self::as_f32(mu, fl0)? + Self::as_f32(mu, fl1)?);
                     Ok(())
                 }
                 _ => Err(Exception::new(Condition::Type, "mu:fl-add", fl1)),
@@ -92,7 +92,7 @@ impl MuFunction for Float {
         match Tag::type_of(mu, fl0) {
             Type::Float => match Tag::type_of(mu, fl1) {
                 Type::Float => {
-                    fp.value = Self::as_tag(Self::as_f32(mu, fl0) - Self::as_f32(mu, fl1));
+                    fp.value = Self::as_tag(Self::as_f32(mu, fl0)? - Self::as_f32(mu, fl1)?);
                     Ok(())
                 }
                 _ => Err(Exception::new(Condition::Type, "mu:fl-sub", fl1)),
@@ -108,7 +108,7 @@ impl MuFunction for Float {
         match Tag::type_of(mu, fl0) {
             Type::Float => match Tag::type_of(mu, fl1) {
                 Type::Float => {
-                    fp.value = Self::as_tag(Self::as_f32(mu, fl0) * Self::as_f32(mu, fl1));
+                    fp.value = Self::as_tag(Self::as_f32(mu, fl0)? * Self::as_f32(mu, fl1)?);
                     Ok(())
                 }
                 _ => Err(Exception::new(Condition::Type, "mu:fl-mul", fl1)),
@@ -124,7 +124,7 @@ impl MuFunction for Float {
         match Tag::type_of(mu, fl0) {
             Type::Float => match Tag::type_of(mu, fl1) {
                 Type::Float => {
-                    fp.value = if Self::as_f32(mu, fl0) < Self::as_f32(mu, fl1) {
+                    fp.value = if Self::as_f32(mu, fl0)? < Self::as_f32(mu, fl1)? {
                         Tag::t()
                     } else {
                         Tag::nil()
@@ -145,7 +145,7 @@ impl MuFunction for Float {
         match Tag::type_of(mu, fl0) {
             Type::Float => match Tag::type_of(mu, fl1) {
                 Type::Float => {
-                    fp.value = Self::as_tag(Self::as_f32(mu, fl0) / Self::as_f32(mu, fl1));
+                    fp.value = Self::as_tag(Self::as_f32(mu, fl0)? / Self::as_f32(mu, fl1)?);
                     Ok(())
                 }
                 _ => Err(Exception::new(Condition::Type, "mu:fl-div", fl1)),
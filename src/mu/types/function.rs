@@ -12,9 +12,12 @@ use {
         },
         image,
         types::{
+            cons::{Cons, Core as _, ProperListIter},
             fixnum::Fixnum,
             r#struct::Struct,
             symbol::{Core as _, Symbol},
+            vecimage::{TypedVec, VecType},
+            vector::{Core as _, Vector},
         },
     },
     std::cell::RefMut,
@@ -26,15 +29,73 @@ pub struct Function {
     nreq: Tag,   // fixnum # of required arguments
     form: Tag,   // cons body or fixnum native table offset
     frame: Tag,  // frame id
+    code: Tag,   // byte vector of compiled body or nil
+    env: Tag,    // captured frame ids, or frames once closed over
+    arity: Tag,  // nil, or vector describing arguments past the required
+}
+
+// &optional, &rest, and &key arguments
+pub struct Arity {
+    pub nopt: usize,        // number of optionals
+    pub rest: bool,         // &rest argument
+    pub keys: Vec<Tag>,     // &key keywords
+    pub defaults: Vec<Tag>, // default forms, optionals then keys
+}
+
+impl Arity {
+    pub fn evict(&self, mu: &Mu) -> Tag {
+        let vec = vec![
+            Fixnum::as_tag(self.nopt as i64),
+            if self.rest {
+                Symbol::keyword("t")
+            } else {
+                Tag::nil()
+            },
+            Cons::list(mu, &self.keys),
+            Cons::list(mu, &self.defaults),
+        ];
+
+        TypedVec::<Vec<Tag>> { vec }.vec.to_vector().evict(mu)
+    }
+
+    pub fn from_tag(mu: &Mu, arity: Tag) -> Option<Self> {
+        if arity.null_() {
+            return None;
+        }
+
+        let list = |nth| {
+            ProperListIter::new(mu, Vector::r#ref(mu, arity, nth).unwrap())
+                .map(|cons| Cons::car(mu, cons))
+                .collect()
+        };
+
+        Some(Arity {
+            nopt: Fixnum::as_i64(mu, Vector::r#ref(mu, arity, 0).unwrap()) as usize,
+            rest: !Vector::r#ref(mu, arity, 1).unwrap().null_(),
+            keys: list(2),
+            defaults: list(3),
+        })
+    }
 }
 
 impl Function {
-    pub fn new(lambda: Tag, nreq: Tag, form: Tag, frame: Tag) -> Self {
+    pub fn new(
+        lambda: Tag,
+        nreq: Tag,
+        form: Tag,
+        frame: Tag,
+        code: Tag,
+        env: Tag,
+        arity: Tag,
+    ) -> Self {
         Function {
             lambda,
             nreq,
             form,
             frame,
+            code,
+            env,
+            arity,
         }
     }
 
@@ -44,6 +105,9 @@ impl Function {
             self.nreq.as_slice(),
             self.form.as_slice(),
             self.frame.as_slice(),
+            self.code.as_slice(),
+            self.env.as_slice(),
+            self.arity.as_slice(),
         ];
 
         let mut heap_ref: RefMut<image::heap::Heap> = mu.heap.borrow_mut();
@@ -72,6 +136,15 @@ impl Function {
                         frame: Tag::from_slice(
                             heap_ref.of_length(main.offset() as usize + 24, 8).unwrap(),
                         ),
+                        code: Tag::from_slice(
+                            heap_ref.of_length(main.offset() as usize + 32, 8).unwrap(),
+                        ),
+                        env: Tag::from_slice(
+                            heap_ref.of_length(main.offset() as usize + 40, 8).unwrap(),
+                        ),
+                        arity: Tag::from_slice(
+                            heap_ref.of_length(main.offset() as usize + 48, 8).unwrap(),
+                        ),
                     }
                 }
                 _ => panic!(),
@@ -119,6 +192,36 @@ impl Function {
             _ => panic!(),
         }
     }
+
+    pub fn code_of(mu: &Mu, func: Tag) -> Tag {
+        match Tag::type_of(mu, func) {
+            Type::Function => match func {
+                Tag::Indirect(_) => Self::to_image(mu, func).code,
+                _ => panic!(),
+            },
+            _ => panic!(),
+        }
+    }
+
+    pub fn env_of(mu: &Mu, func: Tag) -> Tag {
+        match Tag::type_of(mu, func) {
+            Type::Function => match func {
+                Tag::Indirect(_) => Self::to_image(mu, func).env,
+                _ => panic!(),
+            },
+            _ => panic!(),
+        }
+    }
+
+    pub fn arity_of(mu: &Mu, func: Tag) -> Tag {
+        match Tag::type_of(mu, func) {
+            Type::Function => match func {
+                Tag::Indirect(_) => Self::to_image(mu, func).arity,
+                _ => panic!(),
+            },
+            _ => panic!(),
+        }
+    }
 }
 
 pub trait Core {
@@ -136,6 +239,9 @@ impl Core for Function {
                 Self::nreq_of(mu, func),
                 Self::form_of(mu, func),
                 Self::frame_of(mu, func),
+                Self::code_of(mu, func),
+                Self::env_of(mu, func),
+                Self::arity_of(mu, func),
             ],
         )
     }
@@ -175,7 +281,15 @@ mod tests {
 
     #[test]
     fn as_tag() {
-        match Function::new(Tag::nil(), Fixnum::as_tag(0), Tag::nil(), Tag::nil()) {
+        match Function::new(
+            Tag::nil(),
+            Fixnum::as_tag(0),
+            Tag::nil(),
+            Tag::nil(),
+            Tag::nil(),
+            Tag::nil(),
+            Tag::nil(),
+        ) {
             _ => assert_eq!(true, true),
         }
     }
@@ -359,7 +359,7 @@ impl MuFunction for Namespace {
             Type::Vector => match Tag::type_of(mu, import) {
                 Type::Null | Type::Namespace => {
                     fp.value = Self::new(mu, &Vector::as_string(mu, name), import).evict(mu);
-                    Self::add_ns(mu, fp.value).unwrap();
+                    Self::add_ns(mu, fp.value)?;
                     Ok(())
                 }
                 _ => Err(Exception::new(Condition::Type, "mu:make_ns", import)),
@@ -12,7 +12,7 @@ use {
         core::{
             classes::{Tag, TagIndirect, TagType, Type},
             exception,
-            exception::{Condition, Exception},
+            exception::{Condition, Exception, Location},
             frame::Frame,
             mu::{Core as _, Mu},
         },
@@ -25,6 +25,7 @@ use {
             fixnum::Fixnum,
             r#struct::Struct,
             symbol::{Core as _, Symbol},
+            vecimage::{TypedVec, VecType},
             vector::{Core as _, Vector},
         },
     },
@@ -37,16 +38,23 @@ pub enum Stream {
     Stdin(u8),
     Stdout(),
     Stderr(),
+    Broadcast(Vec<Tag>),
+    Echo(Tag, Tag),
+    TwoWay(Tag, Tag),
+    Concat(Vec<Tag>),
     Indirect(StreamImage),
 }
 
 // stream image
 pub struct StreamImage {
-    source: Tag,    // system file id (fixnum) | nil
+    source: Tag,    // system file id (fixnum) | string | composite (struct)
     count: Tag,     // char count (fixnum)
-    direction: Tag, // :input | :output (keyword)
+    direction: Tag, // :input | :output | :io | :listen (keyword)
     eof: Tag,       // end of file flag (bool)
     unch: Tag,      // pushbask for input streams (() | character)
+    name: Tag,      // file path or socket address (string | nil)
+    line: Tag,      // input line number (fixnum)
+    column: Tag,    // input column number (fixnum)
 }
 
 impl Stream {
@@ -59,6 +67,9 @@ impl Stream {
                     image.direction.as_slice(),
                     image.eof.as_slice(),
                     image.unch.as_slice(),
+                    image.name.as_slice(),
+                    image.line.as_slice(),
+                    image.column.as_slice(),
                 ];
 
                 let mut heap_ref: RefMut<image::heap::Heap> = mu.heap.borrow_mut();
@@ -68,11 +79,174 @@ impl Stream {
                         .with_tag(TagType::Heap),
                 )
             }
+            Stream::Broadcast(streams) => {
+                Self::composite(mu, "bcast", "output", streams.to_vec()).evict(mu)
+            }
+            Stream::Echo(input, output) => {
+                Self::composite(mu, "echo", "io", vec![*input, *output]).evict(mu)
+            }
+            Stream::TwoWay(input, output) => {
+                Self::composite(mu, "twoway", "io", vec![*input, *output]).evict(mu)
+            }
+            Stream::Concat(streams) => {
+                Self::composite(mu, "concat", "input", streams.to_vec()).evict(mu)
+            }
+            _ => panic!(),
+        }
+    }
+
+    // composite streams keep their component streams in a struct
+    fn composite(mu: &Mu, kind: &str, direction: &str, streams: Vec<Tag>) -> Stream {
+        Stream::Indirect(StreamImage {
+            source: Struct::to_tag(mu, Symbol::keyword(kind), streams),
+            count: Fixnum::as_tag(0),
+            direction: Symbol::keyword(direction),
+            eof: Tag::nil(),
+            unch: Tag::nil(),
+            name: Tag::nil(),
+            line: Fixnum::as_tag(1),
+            column: Fixnum::as_tag(0),
+        })
+    }
+
+    fn component(mu: &Mu, image: &StreamImage, index: usize) -> Tag {
+        match Vector::r#ref(mu, Struct::vector(mu, image.source), index) {
+            Some(stream) => stream,
+            None => panic!(),
+        }
+    }
+
+    fn is_kind(mu: &Mu, image: &StreamImage, kind: &str) -> bool {
+        Struct::stype(mu, image.source).eq_(Symbol::keyword(kind))
+    }
+
+    // elements of string and byte vector sources
+    fn as_byte(mu: &Mu, tag: Tag) -> u8 {
+        match Tag::type_of(mu, tag) {
+            Type::Char => Char::as_char(mu, tag) as u8,
+            Type::Fixnum => Fixnum::as_i64(mu, tag) as u8,
+            _ => panic!(),
+        }
+    }
+
+    // byte output streams accumulate on top of their initial byte vector
+    fn is_byte_output(mu: &Mu, source: Tag) -> bool {
+        match Tag::type_of(mu, source) {
+            Type::Vector => true,
+            Type::Cons => Tag::type_of(mu, Cons::car(mu, source)) == Type::Fixnum,
+            _ => false,
+        }
+    }
+
+    // characters from the underlying source
+    fn read_source(mu: &Mu, stream: Tag) -> exception::Result<Option<char>> {
+        let system_stream = &mu.system.streams;
+        let mut image = Self::to_image(mu, stream)?;
+        let unch = image.unch;
+
+        if !Self::is_open(mu, stream) {
+            return Err(Exception::new(Condition::Open, "stream::read_char", stream));
+        }
+
+        if image.direction.eq_(Symbol::keyword("output")) {
+            return Err(Exception::new(
+                Condition::Stream,
+                "stream::read_char",
+                stream,
+            ));
+        }
+
+        if Self::is_eof(mu, stream) {
+            return Ok(None);
+        }
+
+        match Tag::type_of(mu, image.source) {
+            Type::Fixnum => {
+                let stream_id = Fixnum::as_i64(mu, image.source) as usize;
+
+                if unch.null_() {
+                    match SystemStream::read_byte(system_stream, stream_id) {
+                        Ok(opt) => match opt {
+                            Some(byte) => Ok(Some(byte as char)),
+                            None => {
+                                image.eof = Tag::t();
+                                Self::update(mu, &image, stream);
+                                Ok(None)
+                            }
+                        },
+                        Err(e) => Err(e),
+                    }
+                } else {
+                    image.unch = Tag::nil();
+                    Self::update(mu, &image, stream);
+
+                    Ok(Some(Char::as_char(mu, unch)))
+                }
+            }
+            Type::Vector => {
+                let mut index = Fixnum::as_i64(mu, image.count) as usize;
+                let length = Vector::length_of(mu, image.source);
+
+                if unch.null_() {
+                    if index == length {
+                        image.eof = Tag::t();
+                        Self::update(mu, &image, stream);
+                        return Ok(None);
+                    }
+
+                    let ch = Vector::r#ref(mu, image.source, index);
+
+                    index += 1;
+
+                    image.count = Fixnum::as_tag(index as i64);
+                    Self::update(mu, &image, stream);
+
+                    match ch {
+                        Some(ch) => Ok(Some(Self::as_byte(mu, ch) as char)),
+                        None => panic!(),
+                    }
+                } else {
+                    image.unch = Tag::nil();
+
+                    if index == length {
+                        image.eof = Tag::t();
+                    }
+
+                    Self::update(mu, &image, stream);
+
+                    Ok(Some(Char::as_char(mu, unch)))
+                }
+            }
+            Type::Struct => {
+                if unch.null_() {
+                    Self::read_composite(mu, stream)
+                } else {
+                    image.unch = Tag::nil();
+                    Self::update(mu, &image, stream);
+
+                    Ok(Some(Char::as_char(mu, unch)))
+                }
+            }
             _ => panic!(),
         }
     }
 
-    pub fn to_image(mu: &Mu, tag: Tag) -> StreamImage {
+    // the output components of a composite stream
+    fn outputs(mu: &Mu, image: &StreamImage) -> Vec<Tag> {
+        if Self::is_kind(mu, image, "bcast") {
+            let streams = Struct::vector(mu, image.source);
+
+            (0..Vector::length_of(mu, streams))
+                .map(|index| Self::component(mu, image, index))
+                .collect()
+        } else if Self::is_kind(mu, image, "concat") {
+            vec![]
+        } else {
+            vec![Self::component(mu, image, 1)]
+        }
+    }
+
+    pub fn to_image(mu: &Mu, tag: Tag) -> exception::Result<StreamImage> {
         match Tag::type_of(mu, tag) {
             Type::Stream => match tag {
                 Tag::Indirect(main) => {
@@ -94,13 +268,22 @@ impl Stream {
                         unch: Tag::from_slice(
                             heap_ref.of_length(main.offset() as usize + 32, 8).unwrap(),
                         ),
+                        name: Tag::from_slice(
+                            heap_ref.of_length(main.offset() as usize + 40, 8).unwrap(),
+                        ),
+                        line: Tag::from_slice(
+                            heap_ref.of_length(main.offset() as usize + 48, 8).unwrap(),
+                        ),
+                        column: Tag::from_slice(
+                            heap_ref.of_length(main.offset() as usize + 56, 8).unwrap(),
+                        ),
                     };
 
-                    image
+                    Ok(image)
                 }
-                _ => panic!(),
+                _ => Err(Exception::new(Condition::Type, "stream::to_image", tag)),
             },
-            _ => panic!(),
+            _ => Err(Exception::new(Condition::Type, "stream::to_image", tag)),
         }
     }
 
@@ -111,6 +294,9 @@ impl Stream {
             image.direction.as_slice(),
             image.eof.as_slice(),
             image.unch.as_slice(),
+            image.name.as_slice(),
+            image.line.as_slice(),
+            image.column.as_slice(),
         ];
 
         let offset = match stream {
@@ -125,26 +311,39 @@ impl Stream {
 
 pub trait Core {
     fn close(_: &Mu, _: Tag);
+    fn flush(_: &Mu, _: Tag) -> exception::Result<()>;
     fn is_eof(_: &Mu, _: Tag) -> bool;
     fn is_open(_: &Mu, _: Tag) -> bool;
+    fn accept(_: &Mu, _: Tag) -> exception::Result<Tag>;
+    fn get_bytes(_: &Mu, _: Tag) -> exception::Result<Tag>;
     fn get_string(_: &Mu, _: Tag) -> exception::Result<Tag>;
     fn open_errout(_: &Mu) -> exception::Result<Tag>;
     fn open_file(_: &Mu, _: &str, is_input: bool) -> exception::Result<Tag>;
+    fn open_socket(_: &Mu, _: &str, is_listener: bool) -> exception::Result<Tag>;
+    fn open_composite(_: &Mu, _: &str, _: Tag) -> exception::Result<Tag>;
     fn open_stdin(_: &Mu) -> exception::Result<Tag>;
     fn open_stdout(_: &Mu) -> exception::Result<Tag>;
     fn open_string(_: &Mu, _: &str, is_input: bool) -> exception::Result<Tag>;
+    fn open_bytes(_: &Mu, _: Tag, is_input: bool) -> exception::Result<Tag>;
     fn read_byte(_: &Mu, _: Tag) -> exception::Result<Option<u8>>;
     fn read_char(_: &Mu, _: Tag) -> exception::Result<Option<char>>;
+    fn location(_: &Mu, _: Tag) -> Location;
+    fn read_bytes(_: &Mu, _: Tag, _: usize) -> exception::Result<Vec<u8>>;
+    fn read_composite(_: &Mu, _: Tag) -> exception::Result<Option<char>>;
     fn unread_char(_: &Mu, _: Tag, _: char) -> exception::Result<Option<()>>;
     fn write(_: &Mu, _: Tag, _: bool, _: Tag) -> exception::Result<()>;
     fn write_byte(_: &Mu, _: Tag, _: u8) -> exception::Result<Option<()>>;
+    fn write_bytes(_: &Mu, _: Tag, _: &[u8]) -> exception::Result<()>;
     fn write_char(_: &Mu, _: Tag, _: char) -> exception::Result<Option<()>>;
     fn view(_: &Mu, _: Tag) -> Tag;
 }
 
 impl Core for Stream {
     fn view(mu: &Mu, stream: Tag) -> Tag {
-        let image = Self::to_image(mu, stream);
+        let image = match Self::to_image(mu, stream) {
+            Ok(image) => image,
+            Err(_) => return Tag::nil(),
+        };
 
         Struct::to_tag(
             mu,
@@ -155,15 +354,21 @@ impl Core for Stream {
                 image.direction,
                 image.eof,
                 image.unch,
+                image.name,
+                image.line,
+                image.column,
             ],
         )
     }
 
     fn is_eof(mu: &Mu, stream: Tag) -> bool {
-        let image = Self::to_image(mu, stream);
+        let image = match Self::to_image(mu, stream) {
+            Ok(image) => image,
+            Err(_) => return true,
+        };
 
         match Tag::type_of(mu, image.direction) {
-            Type::Keyword if image.direction.eq_(Symbol::keyword("input")) => {
+            Type::Keyword if !image.direction.eq_(Symbol::keyword("output")) => {
                 if !image.unch.null_() {
                     false
                 } else {
@@ -175,77 +380,213 @@ impl Core for Stream {
     }
 
     fn is_open(mu: &Mu, stream: Tag) -> bool {
-        let image = Self::to_image(mu, stream);
-
-        !image.source.eq_(Tag::t())
+        Self::to_image(mu, stream).is_ok_and(|image| !image.source.eq_(Tag::t()))
     }
 
     fn close(mu: &Mu, stream: Tag) {
-        let mut image = Self::to_image(mu, stream);
+        let mut image = match Self::to_image(mu, stream) {
+            Ok(image) => image,
+            Err(_) => return,
+        };
 
-        SystemStream::close(
-            &mu.system.streams,
-            Fixnum::as_i64(mu, image.source) as usize,
-        );
+        if let Type::Fixnum = Tag::type_of(mu, image.source) {
+            SystemStream::close(
+                &mu.system.streams,
+                Fixnum::as_i64(mu, image.source) as usize,
+            );
+        }
 
         image.source = Tag::t();
         Self::update(mu, &image, stream);
     }
 
+    fn flush(mu: &Mu, stream: Tag) -> exception::Result<()> {
+        let image = Self::to_image(mu, stream)?;
+
+        if !Self::is_open(mu, stream) {
+            return Ok(());
+        }
+
+        if image.direction.eq_(Symbol::keyword("input")) {
+            return Err(Exception::new(Condition::Stream, "system::flush", stream));
+        }
+
+        match Tag::type_of(mu, image.source) {
+            Type::Fixnum => {
+                let stream_id = Fixnum::as_i64(mu, image.source) as usize;
+                SystemStream::flush(&mu.system.streams, stream_id)
+            }
+            Type::Struct => {
+                for output in Self::outputs(mu, &image) {
+                    Self::flush(mu, output)?
+                }
+            }
+            _ => return Err(Exception::new(Condition::Type, "mu:flush", stream)),
+        }
+
+        Ok(())
+    }
+
     fn get_string(mu: &Mu, tag: Tag) -> exception::Result<Tag> {
         if !Self::is_open(mu, tag) {
             return Err(Exception::new(Condition::Open, "stream::get_string", tag));
         }
 
-        match Tag::type_of(mu, tag) {
-            Type::Stream => {
-                let mut image = Self::to_image(mu, tag);
-                let source = image.source;
-
-                match Tag::type_of(mu, image.direction) {
-                    Type::Keyword if image.direction.eq_(Symbol::keyword("output")) => {
-                        image.source = Tag::nil();
-                        Self::update(mu, &image, tag);
-
-                        match Tag::type_of(mu, source) {
-                            Type::Null => Ok(Vector::from_string("").evict(mu)),
-                            Type::Cons => {
-                                let string = ProperListIter::new(mu, source).fold(
-                                    String::from(""),
-                                    |mut acc, cons| {
-                                        acc.push(Char::as_char(mu, Cons::car(mu, cons)));
-                                        acc
-                                    },
-                                );
-
-                                Ok(
-                                    Vector::from_string(&string.chars().rev().collect::<String>())
-                                        .evict(mu),
-                                )
-                            }
-                            _ => Err(Exception::new(Condition::Type, "stream::get_string", tag)),
-                        }
+        let mut image = Self::to_image(mu, tag)?;
+        let source = image.source;
+
+        if !image.direction.eq_(Symbol::keyword("output")) {
+            return Err(Exception::new(Condition::Type, "stream::get_string", tag));
+        }
+
+        match Tag::type_of(mu, source) {
+            Type::Null => Ok(Vector::from_string("").evict(mu)),
+            Type::Cons if !Self::is_byte_output(mu, source) => {
+                image.source = Tag::nil();
+                Self::update(mu, &image, tag);
+
+                let string =
+                    ProperListIter::new(mu, source).fold(String::from(""), |mut acc, cons| {
+                        acc.push(Char::as_char(mu, Cons::car(mu, cons)));
+                        acc
+                    });
+
+                Ok(Vector::from_string(&string.chars().rev().collect::<String>()).evict(mu))
+            }
+            _ => Err(Exception::new(Condition::Type, "stream::get_string", tag)),
+        }
+    }
+
+    fn get_bytes(mu: &Mu, tag: Tag) -> exception::Result<Tag> {
+        if !Self::is_open(mu, tag) {
+            return Err(Exception::new(Condition::Open, "stream::get_bytes", tag));
+        }
+
+        let mut image = Self::to_image(mu, tag)?;
+
+        if !image.direction.eq_(Symbol::keyword("output"))
+            || !Self::is_byte_output(mu, image.source)
+        {
+            return Err(Exception::new(Condition::Type, "stream::get_bytes", tag));
+        }
+
+        let mut bytes = Vec::<u8>::new();
+        let mut source = image.source;
+
+        while let Type::Cons = Tag::type_of(mu, source) {
+            bytes.push(Self::as_byte(mu, Cons::car(mu, source)));
+            source = Cons::cdr(mu, source);
+        }
+
+        bytes.extend(
+            (0..Vector::length_of(mu, source))
+                .rev()
+                .map(|index| Self::as_byte(mu, Vector::r#ref(mu, source, index).unwrap())),
+        );
+        bytes.reverse();
+
+        image.source = TypedVec::<Vec<u8>> { vec: vec![] }
+            .vec
+            .to_vector()
+            .evict(mu);
+        Self::update(mu, &image, tag);
+
+        Ok(TypedVec::<Vec<u8>> { vec: bytes }.vec.to_vector().evict(mu))
+    }
+
+    fn read_bytes(mu: &Mu, stream: Tag, nbytes: usize) -> exception::Result<Vec<u8>> {
+        let mut image = Self::to_image(mu, stream)?;
+
+        if !Self::is_open(mu, stream) {
+            return Err(Exception::new(
+                Condition::Open,
+                "stream::read_bytes",
+                stream,
+            ));
+        }
+
+        match Tag::type_of(mu, image.source) {
+            Type::Fixnum
+                if image.unch.null_() && !image.direction.eq_(Symbol::keyword("output")) =>
+            {
+                if Self::is_eof(mu, stream) {
+                    return Ok(vec![]);
+                }
+
+                let stream_id = Fixnum::as_i64(mu, image.source) as usize;
+                let bytes = SystemStream::read_bytes(&mu.system.streams, stream_id, nbytes)?;
+
+                if bytes.len() < nbytes {
+                    image.eof = Tag::t();
+                    Self::update(mu, &image, stream);
+                }
+
+                Ok(bytes)
+            }
+            _ => {
+                let mut bytes = Vec::<u8>::new();
+
+                while bytes.len() < nbytes {
+                    match Self::read_byte(mu, stream)? {
+                        Some(byte) => bytes.push(byte),
+                        None => break,
                     }
-                    _ => panic!(),
                 }
+
+                Ok(bytes)
+            }
+        }
+    }
+
+    fn write_bytes(mu: &Mu, stream: Tag, bytes: &[u8]) -> exception::Result<()> {
+        let image = Self::to_image(mu, stream)?;
+
+        match Tag::type_of(mu, image.source) {
+            Type::Fixnum
+                if Self::is_open(mu, stream) && !image.direction.eq_(Symbol::keyword("input")) =>
+            {
+                let stream_id = Fixnum::as_i64(mu, image.source) as usize;
+                SystemStream::write_bytes(&mu.system.streams, stream_id, bytes)
+            }
+            _ => {
+                for byte in bytes {
+                    Self::write_byte(mu, stream, *byte)?;
+                }
+
+                Ok(())
             }
-            _ => panic!(),
         }
     }
 
     fn write(mu: &Mu, tag: Tag, _: bool, stream: Tag) -> exception::Result<()> {
         match Tag::type_of(mu, tag) {
             Type::Stream => {
-                let image = Self::to_image(mu, tag);
+                let image = Self::to_image(mu, tag)?;
                 match Tag::type_of(mu, image.source) {
                     Type::Keyword => mu.write_string("#<stream: closed>".to_string(), stream),
                     Type::Fixnum => mu.write_string(
                         format!("#<stream: id: {}>", Fixnum::as_i64(mu, image.source)),
                         stream,
                     ),
+                    Type::Vector if Vector::type_of(mu, image.source) == Type::Byte => {
+                        mu.write_string("#<stream: byte>".to_string(), stream)
+                    }
+                    Type::Cons if Self::is_byte_output(mu, image.source) => {
+                        mu.write_string("#<stream: byte>".to_string(), stream)
+                    }
                     Type::Null | Type::Cons | Type::Vector => {
                         mu.write_string("#<stream: string>".to_string(), stream)
                     }
+                    Type::Struct => mu.write_string(
+                        format!(
+                            "#<stream: {}>",
+                            Vector::as_string(
+                                mu,
+                                Symbol::name_of(mu, Struct::stype(mu, image.source))
+                            )
+                        ),
+                        stream,
+                    ),
                     _ => panic!(
                         "internal: stream type inconsistency {:?}",
                         Tag::type_of(mu, image.source)
@@ -258,11 +599,147 @@ impl Core for Stream {
 
     fn open_file(mu: &Mu, path: &str, is_input: bool) -> exception::Result<Tag> {
         let stream = Stream::File(path.to_string(), is_input, 0);
-        let id = SystemStream::open(&mu.system.streams, path, is_input).unwrap();
+        let id = SystemStream::open(&mu.system.streams, path, is_input)?;
+
+        let image = StreamImage {
+            source: Fixnum::as_tag(id as i64),
+            count: Fixnum::as_tag(0),
+            direction: if is_input {
+                Symbol::keyword("input")
+            } else {
+                Symbol::keyword("output")
+            },
+            eof: Tag::nil(),
+            unch: Tag::nil(),
+            name: Vector::from_string(path).evict(mu),
+            line: Fixnum::as_tag(1),
+            column: Fixnum::as_tag(0),
+        };
+
+        Ok(Stream::Indirect(image).evict(mu))
+    }
+
+    fn open_socket(mu: &Mu, addr: &str, is_listener: bool) -> exception::Result<Tag> {
+        let system_stream = &mu.system.streams;
+
+        let id = if is_listener {
+            SystemStream::listen(system_stream, addr)
+        } else {
+            SystemStream::connect(system_stream, addr)
+        };
+
+        let image = StreamImage {
+            source: Fixnum::as_tag(id? as i64),
+            count: Fixnum::as_tag(0),
+            direction: if is_listener {
+                Symbol::keyword("listen")
+            } else {
+                Symbol::keyword("io")
+            },
+            eof: Tag::nil(),
+            unch: Tag::nil(),
+            name: Vector::from_string(addr).evict(mu),
+            line: Fixnum::as_tag(1),
+            column: Fixnum::as_tag(0),
+        };
+
+        Ok(Stream::Indirect(image).evict(mu))
+    }
+
+    fn accept(mu: &Mu, listener: Tag) -> exception::Result<Tag> {
+        let image = Self::to_image(mu, listener)?;
+
+        if !Self::is_open(mu, listener) {
+            return Err(Exception::new(Condition::Open, "stream::accept", listener));
+        }
+
+        if !image.direction.eq_(Symbol::keyword("listen")) {
+            return Err(Exception::new(
+                Condition::Stream,
+                "stream::accept",
+                listener,
+            ));
+        }
+
+        let id = match SystemStream::accept(
+            &mu.system.streams,
+            Fixnum::as_i64(mu, image.source) as usize,
+        ) {
+            Ok(id) => id,
+            Err(e) => return Err(Exception::new(e.condition, "stream::accept", listener)),
+        };
 
         let image = StreamImage {
             source: Fixnum::as_tag(id as i64),
             count: Fixnum::as_tag(0),
+            direction: Symbol::keyword("io"),
+            eof: Tag::nil(),
+            unch: Tag::nil(),
+            name: image.name,
+            line: Fixnum::as_tag(1),
+            column: Fixnum::as_tag(0),
+        };
+
+        Ok(Stream::Indirect(image).evict(mu))
+    }
+
+    fn open_composite(mu: &Mu, kind: &str, list: Tag) -> exception::Result<Tag> {
+        let streams = match Tag::type_of(mu, list) {
+            Type::Null | Type::Cons => ProperListIter::new(mu, list)
+                .map(|cons| Cons::car(mu, cons))
+                .collect::<Vec<Tag>>(),
+            _ => return Err(Exception::new(Condition::Type, "mu:open", list)),
+        };
+
+        for stream in &streams {
+            if Tag::type_of(mu, *stream) != Type::Stream {
+                return Err(Exception::new(Condition::Type, "mu:open", *stream));
+            }
+        }
+
+        let is_input = |stream: Tag| {
+            Self::to_image(mu, stream).is_ok_and(|image| {
+                image.direction.eq_(Symbol::keyword("input"))
+                    || image.direction.eq_(Symbol::keyword("io"))
+            })
+        };
+
+        let is_output = |stream: Tag| {
+            Self::to_image(mu, stream).is_ok_and(|image| {
+                image.direction.eq_(Symbol::keyword("output"))
+                    || image.direction.eq_(Symbol::keyword("io"))
+            })
+        };
+
+        let mismatch = match kind {
+            "bcast" => streams.iter().find(|stream| !is_output(**stream)),
+            "concat" => streams.iter().find(|stream| !is_input(**stream)),
+            _ if streams.len() != 2 => {
+                return Err(Exception::new(Condition::Stream, "mu:open", list))
+            }
+            _ if !is_input(streams[0]) => Some(&streams[0]),
+            _ if !is_output(streams[1]) => Some(&streams[1]),
+            _ => None,
+        };
+
+        if let Some(stream) = mismatch {
+            return Err(Exception::new(Condition::Stream, "mu:open", *stream));
+        }
+
+        let composite = match kind {
+            "bcast" => Stream::Broadcast(streams),
+            "concat" => Stream::Concat(streams),
+            "echo" => Stream::Echo(streams[0], streams[1]),
+            _ => Stream::TwoWay(streams[0], streams[1]),
+        };
+
+        Ok(composite.evict(mu))
+    }
+
+    fn open_bytes(mu: &Mu, bytes: Tag, is_input: bool) -> exception::Result<Tag> {
+        let image = StreamImage {
+            source: bytes,
+            count: Fixnum::as_tag(0),
             direction: if is_input {
                 Symbol::keyword("input")
             } else {
@@ -270,6 +747,9 @@ impl Core for Stream {
             },
             eof: Tag::nil(),
             unch: Tag::nil(),
+            name: Tag::nil(),
+            line: Fixnum::as_tag(1),
+            column: Fixnum::as_tag(0),
         };
 
         Ok(Stream::Indirect(image).evict(mu))
@@ -298,6 +778,9 @@ impl Core for Stream {
             },
             eof: Tag::nil(),
             unch: Tag::nil(),
+            name: Tag::nil(),
+            line: Fixnum::as_tag(1),
+            column: Fixnum::as_tag(0),
         };
 
         Ok(Stream::Indirect(image).evict(mu))
@@ -310,6 +793,9 @@ impl Core for Stream {
             direction: Symbol::keyword("input"),
             eof: Tag::nil(),
             unch: Tag::nil(),
+            name: Tag::nil(),
+            line: Fixnum::as_tag(1),
+            column: Fixnum::as_tag(0),
         };
 
         Ok(Stream::Indirect(image).evict(mu))
@@ -322,6 +808,9 @@ impl Core for Stream {
             direction: Symbol::keyword("output"),
             eof: Tag::nil(),
             unch: Tag::nil(),
+            name: Tag::nil(),
+            line: Fixnum::as_tag(1),
+            column: Fixnum::as_tag(0),
         };
 
         Ok(Stream::Indirect(image).evict(mu))
@@ -334,96 +823,100 @@ impl Core for Stream {
             direction: Symbol::keyword("output"),
             eof: Tag::nil(),
             unch: Tag::nil(),
+            name: Tag::nil(),
+            line: Fixnum::as_tag(1),
+            column: Fixnum::as_tag(0),
         };
 
         Ok(Stream::Indirect(image).evict(mu))
     }
 
     fn read_char(mu: &Mu, stream: Tag) -> exception::Result<Option<char>> {
-        let system_stream = &mu.system.streams;
-        let mut image = Self::to_image(mu, stream);
-        let unch = image.unch;
+        let unch = Self::to_image(mu, stream)?.unch;
+        let ch = Self::read_source(mu, stream)?;
 
-        if !Self::is_open(mu, stream) {
-            return Err(Exception::new(Condition::Open, "stream::read_char", stream));
-        }
+        // pushed back characters were counted when first read
+        if let (true, Some(ch)) = (unch.null_(), ch) {
+            let mut image = Self::to_image(mu, stream)?;
 
-        if image.direction.eq_(Symbol::keyword("output")) {
-            return Err(Exception::new(
-                Condition::Stream,
-                "stream::read_char",
-                stream,
-            ));
-        }
+            if ch == '\n' {
+                image.line = Fixnum::as_tag(Fixnum::as_i64(mu, image.line) + 1);
+                image.column = Fixnum::as_tag(0);
+            } else {
+                image.column = Fixnum::as_tag(Fixnum::as_i64(mu, image.column) + 1);
+            }
 
-        if Self::is_eof(mu, stream) {
-            return Ok(None);
+            Self::update(mu, &image, stream);
         }
 
-        match Tag::type_of(mu, image.source) {
-            Type::Fixnum => {
-                let stream_id = Fixnum::as_i64(mu, image.source) as usize;
-
-                if unch.null_() {
-                    match SystemStream::read_byte(system_stream, stream_id) {
-                        Ok(opt) => match opt {
-                            Some(byte) => Ok(Some(byte as char)),
-                            None => {
-                                image.eof = Tag::t();
-                                Self::update(mu, &image, stream);
-                                Ok(None)
-                            }
-                        },
-                        Err(e) => Err(e),
-                    }
-                } else {
-                    image.unch = Tag::nil();
-                    Self::update(mu, &image, stream);
+        Ok(ch)
+    }
 
-                    Ok(Some(Char::as_char(mu, unch)))
+    fn location(mu: &Mu, stream: Tag) -> Location {
+        let image = match Self::to_image(mu, stream) {
+            Ok(image) => image,
+            Err(_) => {
+                return Location {
+                    name: None,
+                    line: 0,
+                    column: 0,
                 }
             }
-            Type::Vector => {
-                let mut index = Fixnum::as_i64(mu, image.count) as usize;
-                let length = Vector::length_of(mu, image.source);
+        };
 
-                if unch.null_() {
-                    if index == length {
-                        image.eof = Tag::t();
-                        Self::update(mu, &image, stream);
-                        return Ok(None);
-                    }
+        Location {
+            name: match Tag::type_of(mu, image.name) {
+                Type::Vector => Some(Vector::as_string(mu, image.name)),
+                _ => None,
+            },
+            line: Fixnum::as_i64(mu, image.line) as usize,
+            column: Fixnum::as_i64(mu, image.column) as usize,
+        }
+    }
 
-                    let ch = Vector::r#ref(mu, image.source, index);
+    fn read_composite(mu: &Mu, stream: Tag) -> exception::Result<Option<char>> {
+        let mut image = Self::to_image(mu, stream)?;
 
-                    index += 1;
+        if Self::is_kind(mu, &image, "concat") {
+            let streams = Struct::vector(mu, image.source);
+            let mut index = Fixnum::as_i64(mu, image.count) as usize;
 
-                    image.count = Fixnum::as_tag(index as i64);
-                    Self::update(mu, &image, stream);
+            while index < Vector::length_of(mu, streams) {
+                if let Some(ch) = Self::read_char(mu, Self::component(mu, &image, index))? {
+                    return Ok(Some(ch));
+                }
 
-                    match ch {
-                        Some(ch) => Ok(Some(Char::as_char(mu, ch))),
-                        None => panic!(),
-                    }
-                } else {
-                    image.unch = Tag::nil();
+                index += 1;
+                image.count = Fixnum::as_tag(index as i64);
+                Self::update(mu, &image, stream);
+            }
 
-                    if index == length {
-                        image.eof = Tag::t();
-                    }
+            image.eof = Tag::t();
+            Self::update(mu, &image, stream);
 
-                    Self::update(mu, &image, stream);
+            return Ok(None);
+        }
 
-                    Ok(Some(Char::as_char(mu, unch)))
+        match Self::read_char(mu, Self::component(mu, &image, 0))? {
+            Some(ch) => {
+                if Self::is_kind(mu, &image, "echo") {
+                    Self::write_char(mu, Self::component(mu, &image, 1), ch)?;
                 }
+
+                Ok(Some(ch))
+            }
+            None => {
+                image.eof = Tag::t();
+                Self::update(mu, &image, stream);
+
+                Ok(None)
             }
-            _ => panic!(),
         }
     }
 
     fn read_byte(mu: &Mu, stream: Tag) -> exception::Result<Option<u8>> {
         let system_stream = &mu.system.streams;
-        let mut image = Self::to_image(mu, stream);
+        let mut image = Self::to_image(mu, stream)?;
         let unch = image.unch;
 
         if !Self::is_open(mu, stream) {
@@ -483,16 +976,26 @@ impl Core for Stream {
                 Self::update(mu, &image, stream);
 
                 match ch {
-                    Some(ch) => Ok(Some(Char::as_char(mu, ch) as u8)),
+                    Some(ch) => Ok(Some(Self::as_byte(mu, ch))),
                     None => panic!(),
                 }
             }
+            Type::Struct => {
+                if unch.null_() {
+                    Ok(Self::read_composite(mu, stream)?.map(|ch| ch as u8))
+                } else {
+                    image.unch = Tag::nil();
+                    Self::update(mu, &image, stream);
+
+                    Ok(Some(Char::as_char(mu, unch) as u8))
+                }
+            }
             _ => panic!(),
         }
     }
 
     fn unread_char(mu: &Mu, stream: Tag, ch: char) -> exception::Result<Option<()>> {
-        let mut image = Self::to_image(mu, stream);
+        let mut image = Self::to_image(mu, stream)?;
 
         if !Self::is_open(mu, stream) {
             return Err(Exception::new(
@@ -526,7 +1029,7 @@ impl Core for Stream {
 
     fn write_char(mu: &Mu, stream: Tag, ch: char) -> exception::Result<Option<()>> {
         let system_stream = &mu.system.streams;
-        let mut image = Self::to_image(mu, stream);
+        let mut image = Self::to_image(mu, stream)?;
 
         if !Self::is_open(mu, stream) {
             return Err(Exception::new(
@@ -549,20 +1052,33 @@ impl Core for Stream {
                 let stream_id = Fixnum::as_i64(mu, image.source) as usize;
                 SystemStream::write_byte(system_stream, stream_id, ch as u8)
             }
-            Type::Null | Type::Cons => {
-                image.source = Cons::new(Char::as_tag(ch), image.source).evict(mu);
+            Type::Null | Type::Cons | Type::Vector => {
+                let el = if Self::is_byte_output(mu, image.source) {
+                    Fixnum::as_tag(ch as u8 as i64)
+                } else {
+                    Char::as_tag(ch)
+                };
+
+                image.source = Cons::new(el, image.source).evict(mu);
                 image.count = Fixnum::as_tag(Fixnum::as_i64(mu, image.count) + 1);
                 Self::update(mu, &image, stream);
 
                 Ok(None)
             }
+            Type::Struct => {
+                for output in Self::outputs(mu, &image) {
+                    Self::write_char(mu, output, ch)?;
+                }
+
+                Ok(None)
+            }
             _ => panic!(),
         }
     }
 
     fn write_byte(mu: &Mu, stream: Tag, byte: u8) -> exception::Result<Option<()>> {
         let system_stream = &mu.system.streams;
-        let mut image = Self::to_image(mu, stream);
+        let mut image = Self::to_image(mu, stream)?;
 
         if !Self::is_open(mu, stream) {
             return Err(Exception::new(
@@ -585,13 +1101,26 @@ impl Core for Stream {
                 let stream_id = Fixnum::as_i64(mu, image.source) as usize;
                 SystemStream::write_byte(system_stream, stream_id, byte)
             }
-            Type::Null | Type::Cons => {
-                image.source = Cons::new(Fixnum::as_tag(byte as i64), image.source).evict(mu);
+            Type::Null | Type::Cons | Type::Vector => {
+                let el = if Self::is_byte_output(mu, image.source) {
+                    Fixnum::as_tag(byte as i64)
+                } else {
+                    Char::as_tag(byte as char)
+                };
+
+                image.source = Cons::new(el, image.source).evict(mu);
                 image.count = Fixnum::as_tag(Fixnum::as_i64(mu, image.count) + 1);
                 Self::update(mu, &image, stream);
 
                 Ok(None)
             }
+            Type::Struct => {
+                for output in Self::outputs(mu, &image) {
+                    Self::write_byte(mu, output, byte)?;
+                }
+
+                Ok(None)
+            }
             _ => panic!(
                 "internal: {:?} stream state inconsistency",
                 Tag::type_of(mu, image.source)
@@ -601,22 +1130,38 @@ impl Core for Stream {
 }
 
 pub trait MuFunction {
+    fn mu_accept(_: &Mu, _: &mut Frame) -> exception::Result<()>;
     fn mu_close(_: &Mu, _: &mut Frame) -> exception::Result<()>;
     fn mu_eof(_: &Mu, _: &mut Frame) -> exception::Result<()>;
     fn mu_flush(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn mu_get_bytes(_: &Mu, _: &mut Frame) -> exception::Result<()>;
     fn mu_get_string(_: &Mu, _: &mut Frame) -> exception::Result<()>;
     fn mu_open(_: &Mu, _: &mut Frame) -> exception::Result<()>;
     fn mu_openp(_: &Mu, _: &mut Frame) -> exception::Result<()>;
     fn mu_read(_: &Mu, _: &mut Frame) -> exception::Result<()>;
     fn mu_read_byte(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn mu_read_bytes(_: &Mu, _: &mut Frame) -> exception::Result<()>;
     fn mu_read_char(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn mu_stream_loc(_: &Mu, _: &mut Frame) -> exception::Result<()>;
     fn mu_unread_char(_: &Mu, _: &mut Frame) -> exception::Result<()>;
     fn mu_write(_: &Mu, _: &mut Frame) -> exception::Result<()>;
     fn mu_write_byte(_: &Mu, _: &mut Frame) -> exception::Result<()>;
+    fn mu_write_bytes(_: &Mu, _: &mut Frame) -> exception::Result<()>;
     fn mu_write_char(_: &Mu, _: &mut Frame) -> exception::Result<()>;
 }
 
 impl MuFunction for Stream {
+    fn mu_accept(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let stream = fp.argv[0];
+
+        fp.value = match Tag::type_of(mu, stream) {
+            Type::Stream => Self::accept(mu, stream)?,
+            _ => return Err(Exception::new(Condition::Type, "mu:accept", stream)),
+        };
+
+        Ok(())
+    }
+
     fn mu_close(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
         let stream = fp.argv[0];
 
@@ -657,11 +1202,59 @@ impl MuFunction for Stream {
         let st_dir = fp.argv[1];
         let st_arg = fp.argv[2];
 
+        for (kind, direction) in [
+            ("bcast", "output"),
+            ("concat", "input"),
+            ("echo", "io"),
+            ("twoway", "io"),
+        ] {
+            if st_type.eq_(Symbol::keyword(kind)) {
+                if !st_dir.eq_(Symbol::keyword(direction)) {
+                    return Err(Exception::new(Condition::Type, "mu:open", st_dir));
+                }
+
+                fp.value = Self::open_composite(mu, kind, st_arg)?;
+                return Ok(());
+            }
+        }
+
+        if st_type.eq_(Symbol::keyword("byte")) {
+            let is_input = match Tag::type_of(mu, st_dir) {
+                Type::Keyword if st_dir.eq_(Symbol::keyword("input")) => true,
+                Type::Keyword if st_dir.eq_(Symbol::keyword("output")) => false,
+                _ => return Err(Exception::new(Condition::Type, "mu:open", st_dir)),
+            };
+
+            fp.value = match Tag::type_of(mu, st_arg) {
+                Type::Vector if Vector::type_of(mu, st_arg) == Type::Byte => {
+                    Self::open_bytes(mu, st_arg, is_input)?
+                }
+                _ => return Err(Exception::new(Condition::Type, "mu:open", st_arg)),
+            };
+
+            return Ok(());
+        }
+
         let arg = match Tag::type_of(mu, st_arg) {
             Type::Vector => Vector::as_string(mu, st_arg),
             _ => return Err(Exception::new(Condition::Type, "mu:open", st_arg)),
         };
 
+        if st_type.eq_(Symbol::keyword("socket")) {
+            let is_listener = match Tag::type_of(mu, st_dir) {
+                Type::Keyword if st_dir.eq_(Symbol::keyword("listen")) => true,
+                Type::Keyword if st_dir.eq_(Symbol::keyword("connect")) => false,
+                _ => return Err(Exception::new(Condition::Type, "mu:open", st_dir)),
+            };
+
+            fp.value = match Self::open_socket(mu, &arg, is_listener) {
+                Ok(stream) => stream,
+                Err(e) => return Err(Exception::new(e.condition, "mu:open", st_arg)),
+            };
+
+            return Ok(());
+        }
+
         let dir = match Tag::type_of(mu, st_dir) {
             Type::Keyword if st_dir.eq_(Symbol::keyword("input")) => true,
             Type::Keyword if st_dir.eq_(Symbol::keyword("output")) => false,
@@ -670,11 +1263,14 @@ impl MuFunction for Stream {
 
         match Tag::type_of(mu, st_type) {
             Type::Keyword if st_type.eq_(Symbol::keyword("file")) => {
-                fp.value = Self::open_file(mu, &arg, dir).unwrap();
+                fp.value = match Self::open_file(mu, &arg, dir) {
+                    Ok(stream) => stream,
+                    Err(e) => return Err(Exception::new(e.condition, "mu:open", st_arg)),
+                };
                 Ok(())
             }
             Type::Keyword if st_type.eq_(Symbol::keyword("string")) => {
-                fp.value = Self::open_string(mu, &arg, dir).unwrap();
+                fp.value = Self::open_string(mu, &arg, dir)?;
                 Ok(())
             }
             _ => Err(Exception::new(Condition::Type, "mu:open", st_type)),
@@ -684,28 +1280,12 @@ impl MuFunction for Stream {
     fn mu_flush(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
         let stream = fp.argv[0];
 
-        let system_stream = &mu.system.streams;
-        let image = Self::to_image(mu, stream);
-
         fp.value = Tag::nil();
 
-        if !Self::is_open(mu, stream) {
-            return Ok(());
-        }
-
-        if image.direction.eq_(Symbol::keyword("input")) {
-            return Err(Exception::new(Condition::Stream, "system::flush", stream));
-        }
-
-        match Tag::type_of(mu, image.source) {
-            Type::Fixnum => {
-                let stream_id = Fixnum::as_i64(mu, image.source) as usize;
-                SystemStream::flush(system_stream, stream_id)
-            }
-            _ => return Err(Exception::new(Condition::Type, "mu:flush", stream)),
+        match Tag::type_of(mu, stream) {
+            Type::Stream => Self::flush(mu, stream),
+            _ => Err(Exception::new(Condition::Type, "mu:flush", stream)),
         }
-
-        Ok(())
     }
 
     fn mu_read(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
@@ -714,9 +1294,16 @@ impl MuFunction for Stream {
         let eof_value = fp.argv[2];
 
         match Tag::type_of(mu, stream) {
+            // the second value is :t if the stream was at end of file
             Type::Stream => match mu.read(stream, !eofp.null_(), eof_value) {
                 Ok(tag) => {
-                    fp.value = tag;
+                    let eof = if tag.eq_(eof_value) && mu.eof(stream) {
+                        Tag::t()
+                    } else {
+                        Tag::nil()
+                    };
+
+                    fp.value = mu.set_values(vec![tag, eof]);
                     Ok(())
                 }
                 Err(e) => Err(e),
@@ -773,14 +1360,99 @@ impl MuFunction for Stream {
         }
     }
 
+    fn mu_get_bytes(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let stream = fp.argv[0];
+
+        match Tag::type_of(mu, stream) {
+            Type::Stream => {
+                fp.value = Self::get_bytes(mu, stream)?;
+                Ok(())
+            }
+            _ => Err(Exception::new(Condition::Type, "mu:get-bytes", stream)),
+        }
+    }
+
+    fn mu_read_bytes(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let stream = fp.argv[0];
+        let nbytes = fp.argv[1];
+
+        let nbytes = match Tag::type_of(mu, nbytes) {
+            Type::Fixnum if Fixnum::as_i64(mu, nbytes) >= 0 => Fixnum::as_i64(mu, nbytes),
+            _ => return Err(Exception::new(Condition::Type, "mu:rd-bytes", nbytes)),
+        };
+
+        // the result has to fit in the heap
+        if nbytes as usize > mu.heap.borrow().size {
+            return Err(Exception::new(Condition::Range, "mu:rd-bytes", fp.argv[1]));
+        }
+
+        match Tag::type_of(mu, stream) {
+            Type::Stream => {
+                let vec = Self::read_bytes(mu, stream, nbytes as usize)?;
+
+                fp.value = TypedVec::<Vec<u8>> { vec }.vec.to_vector().evict(mu);
+                Ok(())
+            }
+            _ => Err(Exception::new(Condition::Type, "mu:rd-bytes", stream)),
+        }
+    }
+
+    fn mu_write_bytes(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let bytes = fp.argv[0];
+        let stream = fp.argv[1];
+
+        match Tag::type_of(mu, bytes) {
+            Type::Vector if Vector::type_of(mu, bytes) == Type::Byte => (),
+            _ => return Err(Exception::new(Condition::Type, "mu:wr-bytes", bytes)),
+        }
+
+        match Tag::type_of(mu, stream) {
+            Type::Stream => {
+                let vec = (0..Vector::length_of(mu, bytes))
+                    .map(|index| Self::as_byte(mu, Vector::r#ref(mu, bytes, index).unwrap()))
+                    .collect::<Vec<u8>>();
+
+                Self::write_bytes(mu, stream, &vec)?;
+
+                fp.value = bytes;
+                Ok(())
+            }
+            _ => Err(Exception::new(Condition::Type, "mu:wr-bytes", stream)),
+        }
+    }
+
+    fn mu_stream_loc(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
+        let stream = fp.argv[0];
+
+        fp.value = match Tag::type_of(mu, stream) {
+            Type::Stream => {
+                let image = Self::to_image(mu, stream)?;
+
+                Struct::to_tag(
+                    mu,
+                    Symbol::keyword("loc"),
+                    vec![image.name, image.line, image.column],
+                )
+            }
+            _ => return Err(Exception::new(Condition::Type, "mu:stream-loc", stream)),
+        };
+
+        Ok(())
+    }
+
     fn mu_read_char(mu: &Mu, fp: &mut Frame) -> exception::Result<()> {
         let stream = fp.argv[0];
         let eoferrp = fp.argv[1];
         let eof_value = fp.argv[2];
 
+        // the line and column after the char are second and third values
         fp.value = match Tag::type_of(mu, stream) {
             Type::Stream => match Self::read_char(mu, stream) {
-                Ok(Some(ch)) => Char::as_tag(ch),
+                Ok(Some(ch)) => {
+                    let image = Self::to_image(mu, stream)?;
+
+                    mu.set_values(vec![Char::as_tag(ch), image.line, image.column])
+                }
                 Ok(None) if eoferrp.null_() => eof_value,
                 Ok(None) => return Err(Exception::new(Condition::Eof, "mu:rd-char", stream)),
                 Err(e) => return Err(e),
@@ -814,15 +1486,16 @@ impl MuFunction for Stream {
         let stream = fp.argv[1];
 
         match Tag::type_of(mu, stream) {
-            Type::Stream => match Self::unread_char(mu, stream, Char::as_char(mu, ch)) {
-                Ok(Some(_)) => {
-                    panic!()
-                }
-                Ok(None) => {
-                    fp.value = ch;
-                    Ok(())
-                }
-                Err(e) => Err(e),
+            Type::Stream => match Tag::type_of(mu, ch) {
+                Type::Char => match Self::unread_char(mu, stream, Char::as_char(mu, ch)) {
+                    Ok(Some(_)) => Err(Exception::new(Condition::Stream, "mu:un-char", stream)),
+                    Ok(None) => {
+                        fp.value = ch;
+                        Ok(())
+                    }
+                    Err(e) => Err(e),
+                },
+                _ => Err(Exception::new(Condition::Type, "mu:un-char", ch)),
             },
             _ => Err(Exception::new(Condition::Type, "mu:un-char", stream)),
         }
@@ -833,6 +1506,10 @@ impl MuFunction for Stream {
         let stream = fp.argv[1];
 
         match Tag::type_of(mu, ch) {
+            // streams are byte-wide, a wider char would be silently truncated
+            Type::Char if ch.data(mu) > 0xff => {
+                Err(Exception::new(Condition::Range, "mu:write-char", ch))
+            }
             Type::Char => match Tag::type_of(mu, stream) {
                 Type::Stream => match Self::write_char(mu, stream, Char::as_char(mu, ch)) {
                     Ok(_) => {
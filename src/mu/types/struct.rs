@@ -232,6 +232,11 @@ impl MuFunction for Struct {
         let stype = fp.argv[0];
         let list = fp.argv[1];
 
+        match Tag::type_of(mu, list) {
+            Type::Cons | Type::Null => (),
+            _ => return Err(Exception::new(Condition::Type, "mu:struct", list)),
+        }
+
         fp.value = match Tag::type_of(mu, stype) {
             Type::Keyword => {
                 let mut vec = Vec::new();
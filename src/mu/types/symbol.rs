@@ -101,7 +101,7 @@ impl Symbol {
 
     pub fn namespace_of(mu: &Mu, symbol: Tag) -> Tag {
         match Tag::type_of(mu, symbol) {
-            Type::Keyword => Tag::nil(),
+            Type::Null | Type::Keyword => Tag::nil(),
             Type::Symbol => match symbol {
                 Tag::Indirect(_) => Self::to_image(mu, symbol).namespace,
                 _ => panic!(),
@@ -112,7 +112,7 @@ impl Symbol {
 
     pub fn scope_of(mu: &Mu, symbol: Tag) -> Tag {
         match Tag::type_of(mu, symbol) {
-            Type::Keyword => match symbol {
+            Type::Null | Type::Keyword => match symbol {
                 Tag::Direct(_) => Symbol::keyword("extern"),
                 _ => panic!(),
             },
@@ -126,7 +126,7 @@ impl Symbol {
 
     pub fn name_of(mu: &Mu, symbol: Tag) -> Tag {
         match Tag::type_of(mu, symbol) {
-            Type::Keyword => match symbol {
+            Type::Null | Type::Keyword => match symbol {
                 Tag::Direct(dir) => Tag::to_direct(dir.data(), dir.length(), DirectType::Byte),
                 _ => panic!(),
             },
@@ -138,9 +138,20 @@ impl Symbol {
         }
     }
 
+    pub fn set_value(mu: &Mu, symbol: Tag, value: Tag) {
+        match symbol {
+            Tag::Indirect(main) => {
+                let mut heap_ref: RefMut<image::heap::Heap> = mu.heap.borrow_mut();
+
+                heap_ref.write_image(&[value.as_slice()], main.offset() as usize + 24)
+            }
+            _ => panic!(),
+        }
+    }
+
     pub fn value_of(mu: &Mu, symbol: Tag) -> Tag {
         match Tag::type_of(mu, symbol) {
-            Type::Keyword => symbol,
+            Type::Null | Type::Keyword => symbol,
             Type::Symbol => match symbol {
                 Tag::Indirect(_) => Self::to_image(mu, symbol).value,
                 _ => panic!(),
@@ -217,7 +228,7 @@ impl Core for Symbol {
         match Tag::type_of(mu, symbol) {
             Type::Null | Type::Keyword => match str::from_utf8(&symbol.data(mu).to_le_bytes()) {
                 Ok(s) => {
-                    Stream::write_char(mu, stream, ':').unwrap();
+                    Stream::write_char(mu, stream, ':')?;
                     for nth in 0..symbol.length() {
                         match Stream::write_char(mu, stream, s.as_bytes()[nth as usize] as char) {
                             Ok(_) => (),
@@ -358,6 +369,12 @@ impl MuFunction for Symbol {
             }
             Type::Vector => {
                 let str = Vector::as_string(mu, symbol);
+
+                // keywords are immediate, their names are short
+                if str.is_empty() || str.len() > Tag::DIRECT_STR_MAX {
+                    return Err(Exception::new(Condition::Range, "mu:make-kw", symbol));
+                }
+
                 fp.value = Self::keyword(&str);
                 Ok(())
             }
@@ -48,6 +48,7 @@ pub trait IVector {
     fn image_of(_: &VectorImage) -> Vec<[u8; 8]>;
     fn evict(&self, _: &Mu) -> Tag;
     fn r#ref(_: &Mu, _: Tag, _: usize) -> Option<Tag>;
+    fn set(_: &Mu, _: Tag, _: usize, _: Tag) -> Option<()>;
 }
 
 impl<'a> IVector for IndirectVector<'a> {
@@ -157,6 +158,31 @@ impl<'a> IVector for IndirectVector<'a> {
         }
     }
 
+    // only general vectors are written in place
+    fn set(mu: &Mu, vector: Tag, index: usize, value: Tag) -> Option<()> {
+        let image = Vector::to_image(mu, vector);
+
+        if index >= Fixnum::as_i64(mu, image.length) as usize {
+            return None;
+        }
+
+        match Vector::to_type(image.vtype)? {
+            Type::T => match vector {
+                Tag::Indirect(image) => {
+                    let mut heap_ref: RefMut<image::heap::Heap> = mu.heap.borrow_mut();
+
+                    heap_ref.write_image(
+                        &[value.as_slice()],
+                        image.offset() as usize + Self::IMAGE_NBYTES + (index * 8),
+                    );
+                    Some(())
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
     fn r#ref(mu: &Mu, vector: Tag, index: usize) -> Option<Tag> {
         let image = Vector::to_image(mu, vector);
 
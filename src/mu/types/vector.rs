@@ -90,6 +90,20 @@ impl Vector {
         }
     }
 
+    pub fn as_bytes(mu: &Mu, vector: Tag) -> Vec<u8> {
+        match vector {
+            Tag::Indirect(image) => {
+                let heap_ref: Ref<image::heap::Heap> = mu.heap.borrow();
+
+                heap_ref
+                    .of_length((image.offset() + 16) as usize, Self::length_of(mu, vector))
+                    .unwrap()
+                    .to_vec()
+            }
+            _ => panic!(),
+        }
+    }
+
     pub fn length_of(mu: &Mu, vector: Tag) -> usize {
         match vector {
             Tag::Direct(direct) => direct.length() as usize,
@@ -190,7 +204,7 @@ impl<'a> Core<'a> for Vector {
             Tag::Direct(_) => match str::from_utf8(&vector.data(mu).to_le_bytes()) {
                 Ok(s) => {
                     if escape {
-                        mu.write_string("\"".to_string(), stream).unwrap()
+                        mu.write_string("\"".to_string(), stream)?
                     }
 
                     for nth in 0..vector.length() {
@@ -201,7 +215,7 @@ impl<'a> Core<'a> for Vector {
                     }
 
                     if escape {
-                        mu.write_string("\"".to_string(), stream).unwrap()
+                        mu.write_string("\"".to_string(), stream)?
                     }
 
                     Ok(())
@@ -382,7 +396,7 @@ impl<'a> Core<'a> for Vector {
                             for cons in ProperListIter::new(mu, Cons::cdr(mu, vec_list)) {
                                 let el = Cons::car(mu, cons);
                                 match Tag::type_of(mu, el) {
-                                    Type::Float => vec.push(Float::as_f32(mu, el)),
+                                    Type::Float => vec.push(Float::as_f32(mu, el)?),
                                     _ => {
                                         return Err(Exception::new(
                                             Condition::Type,
@@ -447,6 +461,11 @@ impl MuFunction for Vector {
         let type_sym = fp.argv[0];
         let list = fp.argv[1];
 
+        match Tag::type_of(mu, list) {
+            Type::Cons | Type::Null => (),
+            _ => return Err(Exception::new(Condition::Type, "mu:vector", list)),
+        }
+
         fp.value = match Self::to_type(type_sym) {
             Some(vtype) => match vtype {
                 Type::Null => return Err(Exception::new(Condition::Type, "mu:sv-list", type_sym)),
@@ -518,7 +537,7 @@ impl MuFunction for Vector {
 
                         match Tag::type_of(mu, el) {
                             Type::Float => {
-                                vec.push(Float::as_f32(mu, el));
+                                vec.push(Float::as_f32(mu, el)?);
                             }
                             _ => return Err(Exception::new(Condition::Type, "mu:sv-list", el)),
                         }
@@ -542,6 +561,10 @@ impl MuFunction for Vector {
         let vector = fp.argv[0];
         let index = fp.argv[1];
 
+        if Tag::type_of(mu, vector) != Type::Vector {
+            return Err(Exception::new(Condition::Type, "mu:sv-ref", vector));
+        }
+
         match Tag::type_of(mu, index) {
             Type::Fixnum => {
                 let nth = Fixnum::as_i64(mu, index);
@@ -550,15 +573,12 @@ impl MuFunction for Vector {
                     return Err(Exception::new(Condition::Range, "mu:sv-ref", index));
                 }
 
-                match Tag::type_of(mu, vector) {
-                    Type::Vector => {
-                        fp.value = match Self::r#ref(mu, vector, nth as usize) {
-                            Some(ch) => ch,
-                            None => panic!(),
-                        };
+                match Self::r#ref(mu, vector, nth as usize) {
+                    Some(value) => {
+                        fp.value = value;
                         Ok(())
                     }
-                    _ => Err(Exception::new(Condition::Type, "mu:sv-ref", vector)),
+                    None => Err(Exception::new(Condition::Range, "mu:sv-ref", index)),
                 }
             }
             _ => Err(Exception::new(Condition::Type, "mu:sv-ref", index)),
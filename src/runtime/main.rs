@@ -12,11 +12,29 @@ static GLOBAL: Jemalloc = Jemalloc;
 extern crate mu;
 
 use {
-    crate::mu::core::mu::{Core, Mu, MuCondition},
+    crate::mu::core::{
+        classes::Tag,
+        exception::Exception,
+        frame::Frame,
+        mu::{Core, Mu, MuCondition},
+    },
     getopt::Opt,
-    std::{fs, io::Write},
+    std::{
+        fs,
+        io::Write,
+        sync::atomic::{AtomicBool, Ordering},
+    },
 };
 
+// the interpreter recurses on the native stack, give it room
+// for the configured call depth
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+// native stack used by one level of call depth, with room
+// for unoptimized builds and nested natives
+const FRAME_SIZE: usize = 16 * 1024;
+const MAX_DEPTH: usize = STACK_SIZE / FRAME_SIZE;
+
 // options
 type OptDef = (OptType, String);
 
@@ -29,10 +47,11 @@ enum OptType {
     Pipe,
     Quiet,
     Script,
+    Transcript,
 }
 
 fn options(mut argv: Vec<String>) -> Option<Vec<OptDef>> {
-    let mut opts = getopt::Parser::new(&argv, "h?psdvc:e:l:q:");
+    let mut opts = getopt::Parser::new(&argv, "h?psdvc:e:l:q:t:");
     let mut optv = Vec::new();
 
     loop {
@@ -76,6 +95,9 @@ fn options(mut argv: Vec<String>) -> Option<Vec<OptDef>> {
                     Opt('c', Some(config)) => {
                         optv.push((OptType::Config, config));
                     }
+                    Opt('t', Some(path)) => {
+                        optv.push((OptType::Transcript, path));
+                    }
                     _ => {
                         usage();
                     }
@@ -93,21 +115,49 @@ fn options(mut argv: Vec<String>) -> Option<Vec<OptDef>> {
 }
 
 fn usage() {
-    eprintln!("runtime: {}: [-h?psvcelq] [file...]", <Mu as Core>::VERSION);
+    eprintln!(
+        "runtime: {}: [-h?psvcelqt] [file...]",
+        <Mu as Core>::VERSION
+    );
     eprintln!("?: usage message");
     eprintln!("h: usage message");
-    eprintln!("c: [name:value,...]");
+    eprintln!("c: [name:value,...], depth:N sets the maximum call depth");
+    eprintln!("   warn:on|off|error sets compiler warnings");
     eprintln!("d: debugging on");
     eprintln!("e: eval [form] and print result");
     eprintln!("l: load [path]");
     eprintln!("p: pipe mode");
     eprintln!("q: eval [form] quietly");
     eprintln!("s: script mode");
+    eprintln!("t: transcript repl session to [path]");
     eprintln!("v: print version and exit");
 
     std::process::exit(0);
 }
 
+// the repl reads and writes through a two-way stream that echoes
+// input to the transcript and broadcasts output to it
+fn transcript(mu: &Mu, path: &str) -> Option<Tag> {
+    let transcript_form = "(:lambda (log) \
+           (mu:open :twoway :io \
+             (mu:cons (mu:open :echo :io (mu:cons mu:std-in (mu:cons log ()))) \
+               (mu:cons (mu:open :bcast :output (mu:cons mu:std-out (mu:cons log ()))) ()))))"
+        .to_string();
+
+    let func = mu
+        .eval(mu.compile(mu.read_string(transcript_form).ok()?).ok()?)
+        .ok()?;
+    let log = mu.open_file(path, false).ok()?;
+
+    Frame {
+        func,
+        argv: vec![log],
+        value: Tag::nil(),
+    }
+    .apply(mu, func)
+    .ok()
+}
+
 fn load(mu: &Mu, path: &str, debug: bool) -> Option<()> {
     let about = fs::metadata(path).ok()?;
 
@@ -115,12 +165,14 @@ fn load(mu: &Mu, path: &str, debug: bool) -> Option<()> {
         return None;
     }
 
-    let load_form = "(mu:open :file :input \"".to_string() + path + "\")";
-    let istream = mu.eval(mu.read_string(load_form).unwrap()).unwrap();
+    let istream = mu.open_file(path, true).ok()?;
     let eof_value = mu.read_string(":eof".to_string()).unwrap(); // need make_symbol here
 
     #[allow(clippy::while_let_loop)]
     loop {
+        // where the reader starts on this form, reading consumes it
+        let location = mu.location(istream);
+
         match mu.read(istream, true, eof_value) {
             Ok(form) => {
                 if mu.eq(form, eof_value) {
@@ -147,27 +199,37 @@ fn load(mu: &Mu, path: &str, debug: bool) -> Option<()> {
                                     println!();
                                 }
                             }
-                            Err(e) => {
-                                eprint!(
-                                    "exception: (load eval) raised from {:?}, {:?} condition on ",
-                                    e.source, e.condition
-                                );
-                                mu.write(e.tag, true, mu.errout).unwrap();
+                            Err(mut e) => {
+                                if e.location.is_none() {
+                                    e.location = Some(Box::new(location))
+                                }
+
+                                e.print(mu, mu.errout).unwrap();
                                 break;
                             }
                         }
                     }
                     Err(e) => {
                         eprint!(
-                            "exception: (load compile) raised from {:?}, {:?} condition on ",
-                            e.source, e.condition
+                            "exception: (load compile) {} raised from {:?}, {:?} condition on ",
+                            location, e.source, e.condition
                         );
                         mu.write(e.tag, true, mu.errout).unwrap();
                         break;
                     }
                 }
             }
-            Err(_) => {
+            Err(e) => {
+                eprint!(
+                    "exception: (load read) {} raised from {:?}, {:?} condition on ",
+                    match &e.location {
+                        Some(location) => *location.clone(),
+                        None => mu.location(istream),
+                    },
+                    e.source,
+                    e.condition
+                );
+                mu.write(e.tag, true, mu.errout).unwrap();
                 break;
             }
         }
@@ -176,11 +238,94 @@ fn load(mu: &Mu, path: &str, debug: bool) -> Option<()> {
     Some(())
 }
 
+// set when the debugger abandons a form, the repl has nothing left to say
+static ABANDONED: AtomicBool = AtomicBool::new(false);
+
+// choose a restart for an exception nobody handled
+fn debugger(mu: &Mu, e: &Exception) -> Result<(), Exception> {
+    let restarts = Exception::restarts(mu);
+
+    e.print(mu, mu.errout)?;
+    mu.write_string("\n  0: abandon the form\n".to_string(), mu.errout)?;
+    for (nth, name) in restarts.iter().enumerate() {
+        mu.write_string(format!("  {}: ", nth + 1), mu.errout)?;
+        mu.write(*name, true, mu.errout)?;
+        mu.write_string("\n".to_string(), mu.errout)?;
+    }
+
+    let value = mu.read_string(":value".to_string())?;
+
+    loop {
+        mu.write_string("restart> ".to_string(), mu.stdout)?;
+        std::io::stdout().flush().unwrap();
+
+        let choice = match mu.read(mu.stdin, false, Tag::nil()) {
+            Ok(choice) => choice,
+            Err(_) => return Ok(()),
+        };
+
+        if mu.eq(choice, mu.read_string("0".to_string())?) {
+            ABANDONED.store(true, Ordering::Relaxed);
+            return Ok(());
+        }
+
+        let name = match (1..=restarts.len())
+            .find(|nth| mu.eq(choice, mu.read_string(nth.to_string()).unwrap()))
+        {
+            Some(nth) => restarts[nth - 1],
+            None => continue,
+        };
+
+        if !mu.eq(name, value) {
+            return Err(Exception::invoke_restart(mu, name, &[]));
+        }
+
+        mu.write_string("value> ".to_string(), mu.stdout)?;
+        std::io::stdout().flush().unwrap();
+
+        // errors in the value are reported, not debugged
+        mu.debugger.set(None);
+        let result = mu
+            .read(mu.stdin, false, Tag::nil())
+            .and_then(|form| mu.compile(form))
+            .and_then(|form| mu.eval(form));
+        mu.debugger.set(Some(debugger));
+
+        match result {
+            Ok(form) => return Err(Exception::invoke_restart(mu, name, &[form])),
+            Err(e) => {
+                e.print(mu, mu.errout)?;
+                mu.write_string("\n".to_string(), mu.errout)?
+            }
+        }
+    }
+}
+
 pub fn main() {
+    let runtime = std::thread::Builder::new()
+        .name("runtime".to_string())
+        .stack_size(STACK_SIZE)
+        .spawn(runtime);
+
+    match runtime {
+        Ok(thread) => {
+            if thread.join().is_err() {
+                std::process::exit(-1)
+            }
+        }
+        Err(_) => {
+            eprintln!("runtime: can't create runtime thread");
+            std::process::exit(-1)
+        }
+    }
+}
+
+fn runtime() {
     let mut config = String::new();
     let mut debug = false;
     let mut pipe = false;
     let mut script = false;
+    let mut transcript_stream = None;
 
     match options(std::env::args().collect()) {
         Some(opts) => {
@@ -193,7 +338,17 @@ pub fn main() {
         None => std::process::exit(0),
     }
 
-    let mu = <Mu as Core>::new(config);
+    if let Some(item) = Mu::config_error(&config) {
+        eprintln!("runtime: bad config value {item}");
+        std::process::exit(-1);
+    }
+
+    let mut mu = <Mu as Core>::new(config);
+
+    if mu.max_depth > MAX_DEPTH {
+        eprintln!("runtime: depth clamped to {MAX_DEPTH}");
+        mu.max_depth = MAX_DEPTH;
+    }
 
     match options(std::env::args().collect()) {
         Some(opts) => {
@@ -201,10 +356,11 @@ pub fn main() {
                 match opt.0 {
                     OptType::Eval => {
                         if let Ok(ptr) = mu.read_string(opt.1) {
-                            let form = mu.compile(ptr).unwrap();
-                            if let Ok(eval) = mu.eval(form) {
-                                mu.write(eval, true, mu.stdout).unwrap();
-                                println!();
+                            if let Ok(form) = mu.compile(ptr) {
+                                if let Ok(eval) = mu.eval(form) {
+                                    mu.write(eval, true, mu.stdout).unwrap();
+                                    println!();
+                                }
                             }
                         }
                     }
@@ -230,6 +386,13 @@ pub fn main() {
                             mu.eval(form).unwrap();
                         }
                     }
+                    OptType::Transcript => match transcript(&mu, &opt.1) {
+                        Some(stream) => transcript_stream = Some(stream),
+                        None => {
+                            eprintln!("runtime: failed to open transcript {}", &opt.1);
+                            std::process::exit(-1);
+                        }
+                    },
                     OptType::Config => (),
                 }
             }
@@ -237,7 +400,18 @@ pub fn main() {
         None => std::process::exit(0),
     };
 
+    // forward references resolved by later loads aren't undefined
+    if let Err(e) = mu.warn_undefined() {
+        e.print(&mu, mu.errout).unwrap();
+        std::process::exit(-1);
+    }
+
     if !script {
+        let (repl_in, repl_out) = match transcript_stream {
+            Some(stream) => (stream, stream),
+            None => (mu.stdin, mu.stdout),
+        };
+
         if !pipe {
             println!(
                 "runtime: v{}; config [{}]",
@@ -248,31 +422,40 @@ pub fn main() {
 
         let eof_value = mu.read_string(":eof".to_string()).unwrap(); // need make_symbol here
 
+        if !mu.catch_interrupt() {
+            eprintln!("runtime: can't catch SIGINT");
+        }
+
+        if !pipe {
+            mu.debugger.set(Some(debugger))
+        }
+
         loop {
             if !pipe {
-                mu.write_string("mu> ".to_string(), mu.stdout).unwrap();
+                mu.write_string("mu> ".to_string(), repl_out).unwrap();
                 std::io::stdout().flush().unwrap();
             }
 
-            match mu.read(mu.stdin, true, eof_value) {
+            match mu.read(repl_in, true, eof_value) {
                 Ok(tag) => {
                     if mu.eq(tag, eof_value) {
                         break;
                     }
 
-                    #[allow(clippy::single_match)]
-                    match mu.compile(tag) {
-                        Ok(form) => match mu.eval(form) {
-                            Ok(eval) => mu.write(eval, false, mu.stdout).unwrap(),
-                            Err(e) => {
-                                eprint!(
-                                    "exception: raised from {:?}, {:?} condition on ",
-                                    e.source, e.condition
-                                );
-                                mu.write(e.tag, true, mu.errout).unwrap()
-                            }
-                        },
-                        Err(_) => (),
+                    // an interrupt typed at the prompt doesn't apply to this form
+                    mu.clear_interrupt();
+
+                    match mu.compile(tag).and_then(|form| mu.eval(form)) {
+                        Ok(eval) => mu.write(eval, false, repl_out).unwrap(),
+                        Err(e) if e.condition == MuCondition::Interrupt => {
+                            eprint!(";; interrupted")
+                        }
+                        Err(_) if ABANDONED.swap(false, Ordering::Relaxed) => (),
+                        Err(e) => e.print(&mu, mu.errout).unwrap(),
+                    }
+
+                    if let Err(e) = mu.warn_undefined() {
+                        e.print(&mu, mu.errout).unwrap()
                     }
                 }
                 Err(e) => {
@@ -282,7 +465,7 @@ pub fn main() {
                 }
             }
 
-            println!();
+            mu.write_string("\n".to_string(), repl_out).unwrap();
         }
     }
 }